@@ -1,7 +1,9 @@
 use cgmath::{InnerSpace, Matrix4, Point2, Vector2, Zero};
+use std::fmt;
+use std::str::FromStr;
 use std::time::Duration;
 
-use super::{Scale, TilePos};
+use super::{ParseError, Scale, TilePos};
 
 /// Minimum target width & height, to avoid divide-by-zero errors.
 const MIN_TARGET_SIZE: u32 = 10;
@@ -21,8 +23,34 @@ pub(super) const PIXELS_PER_2X_SCALE: f64 = 400.0;
 /// Distance beneath which to "snap" to the target, for interpolation strategies
 /// like exponential decay that never actually reach their target.
 const INTERPOLATION_DISTANCE_THRESHOLD: f64 = 0.001;
+/// Distance in tiles or base-2 logarithm of scale factor beneath which two
+/// cameras are considered the same, to ignore floating-point error.
+pub const CAMERA_EPSILON: f64 = 1e-9;
 /// Exponential decay constant used for interpolation.
 const INTERPOLATION_DECAY_CONSTANT: f64 = 0.04;
+/// Relative difference between the logarithms of two scale factors beneath
+/// which `average_lerped_scale()` considers them equal, to avoid catastrophic
+/// cancellation.
+const AVERAGE_SCALE_EPSILON: f64 = 1e-6;
+
+/// How long the camera shakes after the player hits a mine.
+pub const SHAKE_DURATION: Duration = Duration::from_millis(300);
+/// Number of back-and-forth motions per second while the camera shakes.
+const SHAKE_FREQUENCY: f64 = 30.0;
+
+/// Returns the offset in pixels at which to draw the grid `time` after the
+/// camera starts shaking. The shake starts at `intensity` pixels and decays to
+/// exactly zero after `SHAKE_DURATION`.
+pub fn shake_offset(time: Duration, intensity: f64) -> Vector2<f64> {
+    if time >= SHAKE_DURATION {
+        return Vector2::zero();
+    }
+    let t = time.as_secs_f64();
+    let amplitude = intensity * (1.0 - t / SHAKE_DURATION.as_secs_f64());
+    // Use different frequencies on each axis so the motion isn't diagonal.
+    let angle = t * SHAKE_FREQUENCY * std::f64::consts::TAU;
+    Vector2::new(angle.sin(), (angle * 1.3).cos()) * amplitude
+}
 
 /// 2D camera.
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -34,6 +62,19 @@ pub struct Camera {
     center: Point2<f64>,
     /// Scale factor.
     scale: Scale,
+
+    /// Whether to render at an exact power-of-two scale, aligned to whole
+    /// pixels.
+    pixel_perfect: bool,
+    /// Whether to offset by half a pixel when the target dimensions are odd,
+    /// so that tile boundaries line up with pixel boundaries.
+    half_pixel_offset: bool,
+    /// Offset in tiles at which the grid is drawn, which does not affect which
+    /// tile is under each pixel.
+    render_offset: Vector2<f64>,
+    /// Offset in tiles by which the camera is shaking, which is added to
+    /// `render_offset`.
+    shake_offset: Vector2<f64>,
 }
 
 impl Default for Camera {
@@ -43,10 +84,50 @@ impl Default for Camera {
 
             center: Point2::new(0.0, 0.0),
             scale: Scale::default(),
+
+            pixel_perfect: false,
+            half_pixel_offset: true,
+            render_offset: Vector2::zero(),
+            shake_offset: Vector2::zero(),
         }
     }
 }
 
+impl fmt::Display for Camera {
+    /// Formats the center of the camera as `x,y`, followed by `,s` where `s` is
+    /// the base-2 logarithm of the scale factor if it is not the default.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.center.x, self.center.y)?;
+        if self.scale != Scale::default() {
+            write!(f, ",{}", self.scale.log2_factor())?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for Camera {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidCoordinates(s.trim().to_owned());
+        let parts = s
+            .split(',')
+            .map(|part| part.trim().parse::<f64>().ok().filter(|x| x.is_finite()))
+            .collect::<Option<Vec<f64>>>()
+            .ok_or_else(invalid)?;
+
+        let mut ret = Self::default();
+        match parts[..] {
+            [x, y] => ret.set_center(Point2::new(x, y)),
+            [x, y, log2_scale] => {
+                ret.set_center(Point2::new(x, y));
+                ret.set_scale(Scale::from_log2_factor(log2_scale));
+            }
+            _ => return Err(invalid()),
+        }
+        Ok(ret)
+    }
+}
+
 impl Camera {
     /// Returns the width and height of the render target.
     pub fn target_dimensions(self) -> (u32, u32) {
@@ -78,6 +159,38 @@ impl Camera {
         self.scale = scale.clamp();
     }
 
+    /// Sets whether the camera renders at an exact power-of-two scale, aligned
+    /// to whole pixels. This is useful for screenshots.
+    pub fn set_pixel_perfect(&mut self, pixel_perfect: bool) {
+        self.pixel_perfect = pixel_perfect;
+    }
+    /// Sets whether the camera offsets by half a pixel when the target
+    /// dimensions are odd. Disabling this makes tiles slightly blurrier along
+    /// odd dimensions, but avoids shimmer on some GPUs when resizing.
+    pub fn set_half_pixel_offset(&mut self, half_pixel_offset: bool) {
+        self.half_pixel_offset = half_pixel_offset;
+    }
+    /// Sets the offset in tiles at which the grid is drawn, such as half a
+    /// tile for variants drawn on a staggered grid. This only affects how the
+    /// grid is drawn, not which tile is under each pixel.
+    pub fn set_render_offset(&mut self, offset: Vector2<f64>) {
+        self.render_offset = offset;
+    }
+    /// Sets the offset in tiles by which the camera is shaking. Like the render
+    /// offset, this only affects how the grid is drawn.
+    pub fn set_shake_offset(&mut self, offset: Vector2<f64>) {
+        self.shake_offset = offset;
+    }
+    /// Returns the scale at which tiles are actually rendered, which is
+    /// rounded to the nearest power of two in pixel-perfect mode.
+    fn render_scale(self) -> Scale {
+        if self.pixel_perfect {
+            Scale::from_log2_factor(self.scale.log2_factor().round())
+        } else {
+            self.scale
+        }
+    }
+
     /// Pans by a number of tiles.
     pub fn pan(&mut self, delta: Vector2<f64>) {
         self.center += delta;
@@ -144,6 +257,28 @@ impl Camera {
         self.scale_by_factor(self.scale().round() / self.scale(), invariant_pos);
         self.set_scale(self.scale().round()); // Fix any potential rounding error.
     }
+    /// Centers on a rectangle of tiles (inclusive) and sets the largest scale
+    /// at which the whole rectangle fits on the screen, leaving `margin_px`
+    /// pixels on each side.
+    ///
+    /// The scale is clamped, so the rectangle may not fit if it is very large.
+    pub fn fit(&mut self, min: TilePos, max: TilePos, margin_px: u32) {
+        let (target_w, target_h) = self.target_dimensions;
+        let margins = margin_px.saturating_mul(2);
+        let available_w = target_w.saturating_sub(margins).max(1) as f64;
+        let available_h = target_h.saturating_sub(margins).max(1) as f64;
+        let tiles_w = (max.0 as f64 - min.0 as f64).abs() + 1.0;
+        let tiles_h = (max.1 as f64 - min.1 as f64).abs() + 1.0;
+
+        self.set_scale(Scale::from_factor(f64::min(
+            available_w / tiles_w,
+            available_h / tiles_h,
+        )));
+        self.set_center(Point2::new(
+            (min.0 as f64 + max.0 as f64 + 1.0) / 2.0,
+            (min.1 as f64 + max.1 as f64 + 1.0) / 2.0,
+        ));
+    }
 
     /// Returns the abstract "distance" between two cameras.
     pub fn distance(a: Self, b: Self) -> f64 {
@@ -194,8 +329,7 @@ impl Camera {
         // panning that many pixels (integrated over the change in scale).
 
         // Interpolate scale factor logarithmically.
-        let delta_scale_factor = b.scale / a.scale;
-        ret.scale_by_factor(delta_scale_factor.powf(t), None);
+        ret.set_scale(Scale::lerp(a.scale, b.scale, t));
 
         // Read the comments in `average_lerped_scale()` before proceeding.
         let avg_scale = average_lerped_scale(a.scale, b.scale);
@@ -219,14 +353,16 @@ impl Camera {
 
         ret
     }
-    /// Advances the camera by one frame toward another camera.
+    /// Advances the camera by one frame toward another camera. Only the
+    /// center and scale are changed.
     ///
     /// Returns `true` if the target has been reached, or `false` otherwise.
     pub fn advance_interpolation(&mut self, target: Self, frame_duration: Duration) -> bool {
-        if *self == target {
-            true
-        } else if Self::distance(*self, target) < INTERPOLATION_DISTANCE_THRESHOLD {
-            *self = target;
+        if self.approx_eq(target, CAMERA_EPSILON)
+            || Self::distance(*self, target) < INTERPOLATION_DISTANCE_THRESHOLD
+        {
+            self.center = target.center;
+            self.scale = target.scale;
             true
         } else {
             let t = frame_duration.as_secs_f64() / INTERPOLATION_DECAY_CONSTANT;
@@ -241,6 +377,15 @@ impl Camera {
         }
     }
 
+    /// Returns `true` if the center and scale of two cameras differ by at most
+    /// `epsilon` tiles and `epsilon` in the base-2 logarithm of the scale
+    /// factor, respectively. Other properties are ignored.
+    pub fn approx_eq(self, other: Self, epsilon: f64) -> bool {
+        (self.center.x - other.center.x).abs() <= epsilon
+            && (self.center.y - other.center.y).abs() <= epsilon
+            && (self.scale.log2_factor() - other.scale.log2_factor()).abs() <= epsilon
+    }
+
     /// Returns an integer tile position near the center of the camera.
     pub fn int_center(self) -> [i32; 2] {
         [self.center.x as i32, self.center.y as i32]
@@ -248,34 +393,43 @@ impl Camera {
 
     /// Returns the tile transform matrix relative to `int_center()`.
     pub fn gl_matrix(self) -> Matrix4<f32> {
+        let scale_matrix = cgmath::Matrix4::from_scale(self.render_scale().factor());
+        let translate_matrix = cgmath::Matrix4::from_translation(self.displacement().extend(0.0));
+        let tile_transform_matrix = (scale_matrix * translate_matrix).cast().unwrap();
+
+        self.projection_matrix() * tile_transform_matrix
+    }
+    /// Returns the number of tiles to translate by, relative to
+    /// `int_center()`.
+    fn displacement(self) -> Vector2<f64> {
+        let scale = self.render_scale();
         let [int_x, int_y] = self.int_center();
         let int_center_f64 = Point2::new(int_x as f64, int_y as f64);
-        let mut displacement = -(self.center - int_center_f64);
-        if self.scale.log2_factor().fract().is_zero() {
+        let mut displacement =
+            -(self.center - int_center_f64) + self.render_offset + self.shake_offset;
+        if scale.log2_factor().fract().is_zero() {
             // When the scale factor is an exact power of two, round to the
             // nearest pixel to make the final image more crisp. This is
             // disabled otherwise because it causes noticeable jiggling during
             // interpolation.
-            let mut pixel_displacement = displacement * self.scale.factor();
+            let mut pixel_displacement = displacement * scale.factor();
             pixel_displacement.x = pixel_displacement.x.round();
             pixel_displacement.y = pixel_displacement.y.round();
             // Offset by half a pixel if the target dimensions are odd, so that
-            // tile boundaries line up with pixel boundaries.
+            // tile boundaries line up with pixel boundaries. This shifts the
+            // whole image, so skip it in pixel-perfect mode.
             let (target_w, target_h) = self.target_dimensions();
-            if target_w % 2 == 1 {
-                pixel_displacement.x += 0.5_f64;
-            }
-            if target_h % 2 == 1 {
-                pixel_displacement.y += 0.5_f64;
+            if self.half_pixel_offset && !self.pixel_perfect {
+                if target_w % 2 == 1 {
+                    pixel_displacement.x += 0.5_f64;
+                }
+                if target_h % 2 == 1 {
+                    pixel_displacement.y += 0.5_f64;
+                }
             }
-            displacement = pixel_displacement / self.scale.factor();
+            displacement = pixel_displacement / scale.factor();
         }
-
-        let scale_matrix = cgmath::Matrix4::from_scale(self.scale.factor());
-        let translate_matrix = cgmath::Matrix4::from_translation(displacement.extend(0.0));
-        let tile_transform_matrix = (scale_matrix * translate_matrix).cast().unwrap();
-
-        self.projection_matrix() * tile_transform_matrix
+        displacement
     }
 
     /// Returns the orthographic projection matrix based on the target
@@ -294,16 +448,183 @@ impl Camera {
         let x = x as f64 - target_w as f64 / 2.0;
         let y = -(y as f64 - target_h as f64 / 2.0);
 
+        let scale = self.render_scale();
         Point2::new(
-            x / self.scale.factor() + self.center.x,
-            y / self.scale.factor() + self.center.y,
+            x / scale.factor() + self.center.x,
+            y / scale.factor() + self.center.y,
         )
     }
+    /// Returns the pixel at some global tile coordinates. This is the inverse
+    /// of `pixel_to_tile_coords()`.
+    ///
+    /// Nothing is drawn in screen space yet; this is for overlays such as
+    /// labels and a minimap.
+    #[allow(dead_code)]
+    pub fn tile_to_pixel(self, tile_coords: Point2<f64>) -> (f64, f64) {
+        let (target_w, target_h) = self.target_dimensions;
+        let scale = self.render_scale();
+        let x = (tile_coords.x - self.center.x) * scale.factor();
+        let y = (tile_coords.y - self.center.y) * scale.factor();
+
+        (x + target_w as f64 / 2.0, -y + target_h as f64 / 2.0)
+    }
     /// Returns the global integer coordinates of the tile containing a pixel.
     pub fn pixel_to_tile_pos(self, pixel: (u32, u32)) -> TilePos {
         let t = self.pixel_to_tile_coords(pixel);
         TilePos(t.x.floor() as i32, t.y.floor() as i32)
     }
+    /// Returns the bottom-left and top-right tiles that are at least partially
+    /// visible.
+    pub fn visible_tile_rect(self) -> (TilePos, TilePos) {
+        let (target_w, target_h) = self.target_dimensions;
+        (
+            self.pixel_to_tile_pos((0, target_h)),
+            self.pixel_to_tile_pos((target_w, 0)),
+        )
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_pixel_perfect_displacement() {
+    let mut camera = Camera::default();
+    camera.set_target_dimensions((101, 100));
+    camera.set_center(Point2::new(0.25, 0.5));
+
+    // Odd dimensions are offset by half a pixel.
+    camera.set_scale(Scale::from_factor(16.0));
+    assert_eq!(camera.displacement(), Vector2::new(-3.5, -8.0) / 16.0);
+    camera.set_pixel_perfect(true);
+    assert_eq!(camera.displacement(), Vector2::new(-4.0, -8.0) / 16.0);
+
+    // Other scales are rounded to the nearest power of two in pixel-perfect
+    // mode.
+    camera.set_scale(Scale::from_factor(20.0));
+    assert_eq!(camera.render_scale(), Scale::from_factor(16.0));
+    assert_eq!(camera.displacement(), Vector2::new(-4.0, -8.0) / 16.0);
+    camera.set_pixel_perfect(false);
+    assert_eq!(camera.render_scale(), Scale::from_factor(20.0));
+    assert_eq!(camera.displacement(), Vector2::new(-0.25, -0.5));
+}
+
+#[cfg(test)]
+#[test]
+fn test_half_pixel_offset_disabled() {
+    let mut camera = Camera::default();
+    camera.set_target_dimensions((101, 99));
+    camera.set_center(Point2::new(0.25, 0.5));
+    camera.set_scale(Scale::from_factor(16.0));
+    assert_eq!(camera.displacement(), Vector2::new(-3.5, -7.5) / 16.0);
+
+    camera.set_half_pixel_offset(false);
+    assert_eq!(camera.displacement(), Vector2::new(-4.0, -8.0) / 16.0);
+
+    // Other scales are unaffected.
+    camera.set_scale(Scale::from_factor(20.0));
+    assert_eq!(camera.displacement(), Vector2::new(-0.25, -0.5));
+}
+
+#[cfg(test)]
+#[test]
+fn test_render_offset_displacement() {
+    let mut camera = Camera::default();
+    camera.set_target_dimensions((100, 100));
+    camera.set_center(Point2::new(0.25, 0.5));
+    let tile_under_corner = camera.pixel_to_tile_pos((0, 0));
+
+    camera.set_render_offset(Vector2::new(0.5, 0.0));
+    assert_eq!(camera.displacement(), Vector2::new(0.25, -0.5));
+    camera.set_scale(Scale::from_factor(20.0));
+    assert_eq!(camera.displacement(), Vector2::new(0.25, -0.5));
+
+    // Shaking adds to the offset instead of replacing it.
+    camera.set_shake_offset(Vector2::new(0.0, 0.25));
+    assert_eq!(camera.displacement(), Vector2::new(0.25, -0.25));
+
+    // The offset is purely visual.
+    camera.set_scale(Scale::from_factor(16.0));
+    assert_eq!(camera.pixel_to_tile_pos((0, 0)), tile_under_corner);
+}
+
+#[cfg(test)]
+#[test]
+fn test_fit() {
+    let mut camera = Camera::default();
+    camera.set_target_dimensions((800, 600));
+
+    // 20x10 tiles in 600x400 pixels.
+    camera.fit(TilePos(-5, 3), TilePos(14, 12), 100);
+    assert!((camera.scale().factor() - 30.0).abs() < 1e-9);
+    assert_eq!(camera.center(), Point2::new(5.0, 8.0));
+    let (x, y) = camera.tile_to_pixel(Point2::new(-5.0, 3.0));
+    assert!((x - 100.0).abs() < 1e-9);
+    assert!((y - 450.0).abs() < 1e-9);
+
+    // A huge margin leaves a single pixel.
+    camera.fit(TilePos(0, 0), TilePos(0, 0), u32::MAX);
+    assert_eq!(camera.scale(), Scale::min());
+
+    // A single tile is limited by the maximum scale.
+    camera.fit(TilePos(2, 2), TilePos(2, 2), 0);
+    assert_eq!(camera.scale(), Scale::max());
+    assert_eq!(camera.center(), Point2::new(2.5, 2.5));
+}
+
+#[cfg(test)]
+#[test]
+fn test_camera_string_round_trip() {
+    let mut camera = Camera::default();
+    assert_eq!(camera.to_string(), "0,0");
+    camera.set_center(Point2::new(-12.5, 3.0));
+    assert_eq!(camera.to_string(), "-12.5,3");
+    for &(x, y, factor) in &[(-12.5, 3.0, 16.0), (0.1, -1e9, 8.0), (-7.0, -0.75, 45.3)] {
+        camera.set_center(Point2::new(x, y));
+        camera.set_scale(Scale::from_factor(factor));
+        assert_eq!(camera.to_string().parse(), Ok(camera));
+    }
+
+    assert_eq!(
+        " 1 , 2 ".parse::<Camera>().unwrap().center(),
+        Point2::new(1.0, 2.0)
+    );
+    for s in &["", "1", "1,", "1,2,3,4", "a,b", "1,inf", "1,2,NaN"] {
+        assert_eq!(
+            s.parse::<Camera>(),
+            Err(ParseError::InvalidCoordinates(s.trim().to_owned())),
+        );
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_tile_to_pixel_round_trip() {
+    let mut camera = Camera::default();
+    camera.set_target_dimensions((641, 480));
+    camera.set_center(Point2::new(-12.25, 300.5));
+    camera.set_scale(Scale::from_log2_factor(4.3));
+
+    for &pixel in &[(0, 0), (640, 479), (320, 240), (17, 401)] {
+        let (x, y) = camera.tile_to_pixel(camera.pixel_to_tile_coords(pixel));
+        assert!((x - pixel.0 as f64).abs() < 1e-9);
+        assert!((y - pixel.1 as f64).abs() < 1e-9);
+    }
+
+    let (x, y) = camera.tile_to_pixel(camera.center());
+    assert_eq!((x, y), (320.5, 240.0));
+}
+
+#[cfg(test)]
+#[test]
+fn test_lerp_scale() {
+    let a = Camera::default();
+    let mut b = Camera::default();
+    b.set_center(Point2::new(-30.0, 12.5));
+    b.set_scale(Scale::from_factor(50.0));
+    for &t in &[0.0, 0.1, 0.5, 0.75, 1.0] {
+        let expected = Scale::lerp(a.scale(), b.scale(), t);
+        let actual = Camera::lerp(a, b, t).scale();
+        assert!((actual.log2_factor() - expected.log2_factor()).abs() < 1e-9);
+    }
 }
 
 /// Returns the "average" scale between the two cameras, averaging scale factor
@@ -337,13 +658,75 @@ fn average_lerped_scale(s1: Scale, s2: Scale) -> Scale {
     // ------ = - -----------------
     // tiles      2^(-s₁) - 2^(-s₂)
 
-    let numerator = 2.0_f64.ln() * (s1.log2_factor() - s2.log2_factor());
+    let (log2_s1, log2_s2) = (s1.log2_factor(), s2.log2_factor());
+    let magnitude = log2_s1.abs().max(log2_s2.abs()).max(1.0);
+    if (log2_s1 - log2_s2).abs() <= AVERAGE_SCALE_EPSILON * magnitude {
+        // The expression is undefined at s₁ = s₂, and when s₁ and s₂ are very
+        // close, both the numerator and the denominator lose most of their
+        // precision. In either case, the midpoint is an excellent
+        // approximation.
+        return Scale::from_log2_factor((log2_s1 + log2_s2) / 2.0);
+    }
+
+    let numerator = 2.0_f64.ln() * (log2_s1 - log2_s2);
     let denominator = s1.inv_factor() - s2.inv_factor();
-    if numerator.is_zero() || denominator.is_zero() {
-        // The expression is undefined at s₁ = s₂, but then the average of s₁
-        // and s₂ is trivial.
-        s1
-    } else {
-        Scale::from_factor(-numerator / denominator)
+    Scale::from_factor(-numerator / denominator)
+}
+
+#[cfg(test)]
+#[test]
+fn test_average_lerped_scale_near_equal() {
+    for &log2_factor in &[-2.0, 0.0, 3.0, 4.5, 6.0] {
+        let s1 = Scale::from_log2_factor(log2_factor);
+        let s2 = Scale::from_log2_factor(log2_factor + 1e-9);
+        for &(a, b) in &[(s1, s2), (s2, s1), (s1, s1)] {
+            let avg = average_lerped_scale(a, b);
+            assert!(avg.log2_factor().is_finite());
+            assert!((avg.log2_factor() - log2_factor).abs() < 1e-6);
+        }
     }
+
+    // Scales that are far apart should still use the exact formula.
+    let avg = average_lerped_scale(Scale::from_factor(8.0), Scale::from_factor(32.0));
+    let expected = 2.0_f64.ln() * 2.0 / (1.0 / 8.0 - 1.0 / 32.0);
+    assert!((avg.factor() - expected).abs() < 1e-9);
+}
+
+#[cfg(test)]
+#[test]
+fn test_shake_offset() {
+    let intensity = 5.0;
+    let mut last_amplitude = intensity;
+    for ms in (0..300).step_by(10) {
+        let time = Duration::from_millis(ms);
+        let amplitude = intensity * (1.0 - time.as_secs_f64() / SHAKE_DURATION.as_secs_f64());
+        assert!(amplitude <= last_amplitude);
+        let offset = shake_offset(time, intensity);
+        assert!(offset.x.abs() <= amplitude + 1e-9);
+        assert!(offset.y.abs() <= amplitude + 1e-9);
+        last_amplitude = amplitude;
+    }
+    assert_eq!(shake_offset(Duration::ZERO, intensity).y, intensity);
+    assert_eq!(shake_offset(SHAKE_DURATION, intensity), Vector2::zero());
+    assert_eq!(
+        shake_offset(Duration::from_secs(5), intensity),
+        Vector2::zero()
+    );
+    assert_eq!(
+        shake_offset(Duration::from_millis(50), 0.0),
+        Vector2::zero()
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_camera_approx_eq() {
+    let a = Camera::default();
+    let mut b = a;
+    b.set_center(Point2::new(1e-12, -1e-12));
+    b.set_scale(Scale::from_log2_factor(a.scale().log2_factor() + 1e-12));
+    assert_ne!(a, b);
+    assert!(a.approx_eq(b, CAMERA_EPSILON));
+    b.pan(Vector2::new(0.01, 0.0));
+    assert!(!a.approx_eq(b, CAMERA_EPSILON));
 }
@@ -1,90 +1,401 @@
 use itertools::Itertools;
-use rand::Rng;
-use std::collections::HashMap;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
 use std::str::FromStr;
 
 use super::tile::{FlagState, HiddenState, PackedTile, Tile};
-use super::MINE_DENSITY;
+use super::{ParseError, MINE_DENSITY};
 
 pub const CHUNK_SIZE_LOG_2: usize = 6;
 pub const CHUNK_SIZE: usize = 2_usize.pow(CHUNK_SIZE_LOG_2 as u32);
 
-#[derive(Debug, Default, Clone)]
-pub struct Grid(HashMap<ChunkPos, Chunk>);
+/// Map from chunk positions to chunks.
+///
+/// Chunks are looked up for nearly every tile access, so this uses a much
+/// faster hash function than the default. Run `cargo test --release --
+/// --ignored bench_chunk_map --nocapture` to compare them.
+type ChunkMap = HashMap<ChunkPos, Chunk, BuildHasherDefault<ChunkPosHasher>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    /// Chunks of tiles.
+    chunks: ChunkMap,
+    /// Seed used to place mines in each chunk.
+    seed: u64,
+    /// Number of flagged tiles, not including question marks.
+    flag_count: usize,
+    /// Number of times mines have been placed in a chunk.
+    generated_chunk_count: usize,
+    /// ID of the reveal action that opened each tile, or `None` if this is not
+    /// being tracked.
+    opened_by: Option<HashMap<TilePos, u32>>,
+    /// ID to give the next reveal action.
+    next_action_id: u32,
+    /// Tiles that have changed since they were last taken, or `None` if
+    /// changes are not being tracked.
+    changed_tiles: Option<HashSet<TilePos>>,
+    /// Value of each tile before it was first changed since the previous
+    /// tiles were last taken, or `None` if this is not being tracked.
+    previous_tiles: Option<HashMap<TilePos, Tile>>,
+    /// Weights of mines that count as something other than one mine in the
+    /// numbers around them. Mines that aren't listed have a weight of 1.
+    mine_weights: HashMap<TilePos, u8>,
+    /// Rectangle outside of which every tile is a wall, or `None` if the grid
+    /// is unbounded.
+    arena: Option<(TilePos, TilePos)>,
+    /// Number of mines in the arena, if it is known.
+    arena_mine_count: Option<usize>,
+}
+impl Default for Grid {
+    fn default() -> Self {
+        Self::with_seed(rand::random())
+    }
+}
 impl fmt::Display for Grid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (ChunkPos(chunk_x, chunk_y), chunk) in &self.0 {
-            write!(f, "@{},{}\n", chunk_x, chunk_y)?;
+        for (pos, weight) in self
+            .mine_weights
+            .iter()
+            .sorted_by_key(|(pos, _)| (pos.0, pos.1))
+        {
+            writeln!(f, "weight {} {}", pos, weight)?;
+        }
+        for (chunk_pos, chunk) in &self.chunks {
+            write!(f, "@{}\n", chunk_pos)?;
             write!(f, "{}\n", chunk)?;
         }
         Ok(())
     }
 }
 impl FromStr for Grid {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut ret = Self::new();
-        for chunk_str in s.split("@") {
+        let mut chunk_strs = s.split("@");
+        // Mine weights come before the first chunk.
+        for line in chunk_strs.next().unwrap_or("").lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (pos, weight) = line
+                .strip_prefix("weight ")
+                .and_then(|rest| rest.split_once(' '))
+                .ok_or(ParseError::InvalidGrid)?;
+            let weight = weight
+                .parse()
+                .ok()
+                .filter(|&w| w > 0)
+                .ok_or(ParseError::InvalidGrid)?;
+            ret.set_mine_weight(pos.parse()?, weight);
+        }
+        for chunk_str in chunk_strs {
             if chunk_str.trim().is_empty() {
                 continue;
             }
-            let rest = chunk_str;
-            let (chunk_x, rest) = rest.split_once(',').ok_or(())?;
-            let (chunk_y, rest) = rest.split_once('\n').ok_or(())?;
+            let (chunk_pos, rest) = chunk_str.split_once('\n').ok_or(ParseError::InvalidGrid)?;
             let chunk = rest.trim().parse()?;
-            ret.0.insert(
-                ChunkPos(
-                    chunk_x.trim().parse().map_err(|_| ())?,
-                    chunk_y.trim().parse().map_err(|_| ())?,
-                ),
-                chunk,
-            );
+            ret.chunks.insert(chunk_pos.parse()?, chunk);
         }
+        ret.flag_count = ret.chunks.values().map(Chunk::flag_count).sum();
         Ok(ret)
     }
 }
 impl Grid {
-    /// Returns a new empty grid.
+    /// Returns a new empty grid with a random seed.
     pub fn new() -> Self {
         Self::default()
     }
+    /// Returns a new empty grid with a specific seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            chunks: ChunkMap::default(),
+            seed,
+            flag_count: 0,
+            generated_chunk_count: 0,
+            opened_by: None,
+            next_action_id: 0,
+            changed_tiles: None,
+            previous_tiles: None,
+            mine_weights: HashMap::new(),
+            arena: None,
+            arena_mine_count: None,
+        }
+    }
+
+    /// Returns the seed used to place mines.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Sets the seed used to place mines. This should only be used to restore
+    /// the seed that mines have already been placed with, such as when loading
+    /// a saved game.
+    pub(super) fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+    /// Removes every chunk and sets the seed used to place mines, keeping
+    /// other settings. Clearing the grid is not recorded as a change.
+    pub fn clear_with_seed(&mut self, seed: u64) {
+        *self = Self {
+            opened_by: self.opened_by.as_ref().map(|_| HashMap::new()),
+            changed_tiles: self.changed_tiles.take(),
+            previous_tiles: self.previous_tiles.as_ref().map(|_| HashMap::new()),
+            arena: self.arena,
+            ..Self::with_seed(seed)
+        };
+    }
 
     /// Returns a chunk of the grid, or `None` if the chunk is missing.
     pub fn get_chunk(&self, pos: ChunkPos) -> Option<&Chunk> {
-        self.0.get(&pos)
+        self.chunks.get(&pos)
     }
     /// Returns a chunk of the grid mutably, filling it with a default if it is
     /// missing.
     pub fn get_chunk_mut(&mut self, pos: ChunkPos) -> &mut Chunk {
-        self.0.entry(pos).or_insert_with(Chunk::default)
+        self.chunks.entry(pos).or_insert_with(Chunk::default)
+    }
+    /// Returns the rectangle, including its corners, outside of which every
+    /// tile is a wall, or `None` if the grid is unbounded.
+    pub fn arena(&self) -> Option<(TilePos, TilePos)> {
+        self.arena
+    }
+    /// Limits play to a rectangle, including `min` and `max`, or removes the
+    /// limit if `None`. Tiles outside the rectangle are walls, which look like
+    /// revealed empty tiles to the rest of the grid but can't be changed, are
+    /// never mines, and never cause mines to be placed around them.
+    pub fn set_arena(&mut self, arena: Option<(TilePos, TilePos)>) {
+        if arena != self.arena {
+            self.arena_mine_count = None;
+        }
+        self.arena = arena;
+    }
+    /// Returns the number of mines in the arena, if there is an arena and its
+    /// mines were placed by `place_exact_mines()`.
+    pub fn arena_mine_count(&self) -> Option<usize> {
+        self.arena_mine_count
+    }
+    /// Returns the number of mines in the arena minus the number of flags,
+    /// which is negative if there are more flags than mines. If the number of
+    /// mines in the arena is not known, this is just the negative number of
+    /// flags.
+    pub fn mines_remaining(&self) -> i64 {
+        self.arena_mine_count.unwrap_or(0) as i64 - self.flag_count as i64
+    }
+    /// Flags every covered tile in the arena and returns the number of tiles
+    /// that were not already flagged. Does nothing and returns 0 if there is no
+    /// arena, since the grid would be infinite.
+    pub fn flag_all_remaining_in_arena(&mut self) -> usize {
+        let (min, max) = match self.arena {
+            Some(arena) => arena,
+            None => return 0,
+        };
+        let mut count = 0;
+        self.for_each_tile_in_rect(min, max, |_, tile| {
+            if let Tile::Covered(f, h) = *tile {
+                if f != FlagState::Flag {
+                    *tile = Tile::Covered(FlagState::Flag, h);
+                    count += 1;
+                }
+            }
+        });
+        count
+    }
+    /// Returns `true` if a tile is inside the arena, or if there is no arena.
+    pub fn in_arena(&self, TilePos(x, y): TilePos) -> bool {
+        match self.arena {
+            Some((TilePos(x1, y1), TilePos(x2, y2))) => x1 <= x && x <= x2 && y1 <= y && y <= y2,
+            None => true,
+        }
+    }
+
+    /// Returns the number of chunks that have been created.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+    /// Returns the minimum and maximum coordinates of chunks that have been
+    /// created, or `None` if there are none. This is cheaper than looking at
+    /// individual tiles when only a rough area is needed, such as to frame an
+    /// overview of the map.
+    pub fn explored_chunk_bounds(&self) -> Option<(ChunkPos, ChunkPos)> {
+        let mut positions = self.chunks.keys();
+        let &first = positions.next()?;
+        Some(positions.fold((first, first), |(min, max), &pos| {
+            (
+                ChunkPos(min.0.min(pos.0), min.1.min(pos.1)),
+                ChunkPos(max.0.max(pos.0), max.1.max(pos.1)),
+            )
+        }))
     }
-    /// Returns a tile in the grid.
+    /// Returns a tile in the grid as it currently appears, without placing
+    /// mines or creating chunks. Missing chunks are treated as covered and
+    /// unknown.
+    ///
+    /// Looking at the grid this way never affects where mines are placed, so
+    /// this is safe to use for anything that only displays the grid.
     pub fn get_tile(&self, pos: TilePos) -> Tile {
+        if !self.in_arena(pos) {
+            return WALL_TILE;
+        }
         match self.get_chunk(pos.chunk()) {
             Some(chunk) => chunk.get_tile(pos),
             None => Tile::default(),
         }
     }
-    /// Sets a tile in the grid.
+    /// Sets a tile in the grid. Walls outside the arena cannot be changed.
     pub fn set_tile(&mut self, pos: TilePos, tile: Tile) {
+        if !self.in_arena(pos) {
+            return;
+        }
+        let old_tile = self.get_tile(pos);
+        if old_tile.is_flagged() {
+            self.flag_count -= 1;
+        }
+        if tile.is_flagged() {
+            self.flag_count += 1;
+        }
+        if old_tile != tile {
+            if let Some(changed_tiles) = &mut self.changed_tiles {
+                changed_tiles.insert(pos);
+            }
+            if let Some(previous_tiles) = &mut self.previous_tiles {
+                previous_tiles.entry(pos).or_insert(old_tile);
+            }
+        }
         self.get_chunk_mut(pos.chunk()).set_tile(pos, tile);
     }
+    /// Calls a function on each tile in a rectangle, including `min` and
+    /// `max`, placing mines in the chunks first. Any changes the function makes
+    /// to a tile are applied as in `set_tile()`, so flag counts and tracked
+    /// changes stay up to date.
+    pub fn for_each_tile_in_rect(
+        &mut self,
+        min: TilePos,
+        max: TilePos,
+        mut f: impl FnMut(TilePos, &mut Tile),
+    ) {
+        for chunk_pos in chunks_in_rect(min, max) {
+            self.place_mines_in_chunk(chunk_pos);
+        }
+        for pos in tiles_in_rect(min, max) {
+            let old_tile = self.get_tile(pos);
+            let mut tile = old_tile;
+            f(pos, &mut tile);
+            if tile != old_tile {
+                self.set_tile(pos, tile);
+            }
+        }
+    }
+
+    /// Enables or disables tracking which tiles change. Disabling it discards
+    /// any changes that have not been taken.
+    ///
+    /// Nothing tracks changes yet; this is for a render cache that only
+    /// redraws the chunks that changed. Until it is enabled, `set_tile()` only
+    /// checks that it is disabled.
+    #[allow(dead_code)]
+    pub fn set_track_changes(&mut self, enabled: bool) {
+        if enabled != self.changed_tiles.is_some() {
+            self.changed_tiles = if enabled { Some(HashSet::new()) } else { None };
+        }
+    }
+    /// Returns the positions of tiles that have been changed by `set_tile()`,
+    /// `reset_flags()`, or anything that calls them since the last call to
+    /// this method, and clears the list. Placing mines in a chunk does not
+    /// count as a change.
+    ///
+    /// Returns an empty set if changes are not being tracked.
+    ///
+    /// Nothing tracks changes yet; see `set_track_changes()`.
+    #[allow(dead_code)]
+    pub fn take_changed_tiles(&mut self) -> HashSet<TilePos> {
+        match &mut self.changed_tiles {
+            Some(changed_tiles) => std::mem::take(changed_tiles),
+            None => HashSet::new(),
+        }
+    }
+
+    /// Returns `true` if the previous values of changed tiles are being
+    /// tracked.
+    pub fn tracks_previous_tiles(&self) -> bool {
+        self.previous_tiles.is_some()
+    }
+    /// Enables or disables tracking the previous values of changed tiles.
+    /// Disabling it discards any previous values that have not been taken.
+    pub fn set_track_previous_tiles(&mut self, enabled: bool) {
+        if enabled != self.tracks_previous_tiles() {
+            self.previous_tiles = if enabled { Some(HashMap::new()) } else { None };
+        }
+    }
+    /// Returns the value that each tile changed by `set_tile()`,
+    /// `reset_flags()`, or anything that calls them had before its first
+    /// change since the last call to this method, and clears the list. Setting
+    /// each tile back to its previous value undoes the changes.
+    ///
+    /// Returns an empty map if previous values are not being tracked.
+    pub fn take_previous_tiles(&mut self) -> HashMap<TilePos, Tile> {
+        match &mut self.previous_tiles {
+            Some(previous_tiles) => std::mem::take(previous_tiles),
+            None => HashMap::new(),
+        }
+    }
+
+    /// Returns the number of revealed tiles.
+    pub fn revealed_count(&self) -> usize {
+        self.chunks.values().map(Chunk::revealed_count).sum()
+    }
+    /// Returns the number of flagged tiles, not including question marks.
+    pub fn flag_count(&self) -> usize {
+        self.flag_count
+    }
+    /// Removes all flags and question marks, without affecting revealed tiles.
+    pub fn reset_flags(&mut self) {
+        for (&chunk_pos, chunk) in &mut self.chunks {
+            for pos in tiles_in_rect(chunk_pos.min_tile(), chunk_pos.max_tile()) {
+                let tile = &mut chunk.tiles[Chunk::index_of_tile(pos)];
+                if let Tile::Covered(f, h) = tile.unpack() {
+                    if f != FlagState::None {
+                        *tile = Tile::Covered(FlagState::None, h).pack();
+                        if let Some(changed_tiles) = &mut self.changed_tiles {
+                            changed_tiles.insert(pos);
+                        }
+                        if let Some(previous_tiles) = &mut self.previous_tiles {
+                            previous_tiles.entry(pos).or_insert(Tile::Covered(f, h));
+                        }
+                    }
+                }
+            }
+        }
+        self.flag_count = 0;
+    }
 
     /// Places mines in unknown squares within a chunk.
+    ///
+    /// Mines are placed deterministically based on the seed of the grid and
+    /// the position of the chunk, so the same chunk in two grids with the same
+    /// seed will always have mines in the same places.
+    ///
+    /// Flags and tiles that are already known to be safe or mines are left as
+    /// they are.
     pub fn place_mines_in_chunk(&mut self, pos: ChunkPos) {
-        // TODO: use a deterministic RNG, seeded using the game seed + chunk pos
-        let mut rng = rand::thread_rng();
+        let mut rng = self.chunk_rng(pos);
         let chunk = self.get_chunk_mut(pos);
         if chunk.all_mines_placed {
             return;
         }
         for tile in &mut chunk.tiles {
+            // Always generate a random value, even if it isn't used, so that
+            // each tile's hidden state does not depend on any other tile.
+            let is_mine = rng.gen_bool(MINE_DENSITY);
             if let Tile::Covered(f, h) = tile.unpack() {
                 if h == HiddenState::Unknown {
-                    let h = if rng.gen_bool(MINE_DENSITY) {
+                    let h = if is_mine {
                         HiddenState::Mine
                     } else {
                         HiddenState::Safe
@@ -94,83 +405,667 @@ impl Grid {
             }
         }
         chunk.all_mines_placed = true;
+        self.generated_chunk_count += 1;
+
+        // Walls are never mines.
+        if self.arena.is_some() {
+            for tile_pos in tiles_in_rect(pos.min_tile(), pos.max_tile()) {
+                if !self.in_arena(tile_pos) {
+                    let tile = &mut self.get_chunk_mut(pos).tiles[Chunk::index_of_tile(tile_pos)];
+                    if let Tile::Covered(f, HiddenState::Mine) = tile.unpack() {
+                        *tile = Tile::Covered(f, HiddenState::Safe).pack();
+                    }
+                }
+            }
+        }
+    }
+    /// Places exactly `count` mines among the covered tiles in a rectangle,
+    /// including its corners, chosen uniformly at random using `seed`. Every
+    /// other covered tile in the rectangle is made safe, and mines are placed
+    /// as usual in the rest of each chunk that overlaps it. Tiles in `safe`
+    /// never become mines, such as the first tile revealed and its neighbors.
+    ///
+    /// Placing mines again replaces the previous ones. If there are fewer
+    /// than `count` covered tiles that can be mines, an error is returned and
+    /// the grid is left unchanged.
+    pub fn place_exact_mines(
+        &mut self,
+        (min, max): (TilePos, TilePos),
+        count: usize,
+        seed: u64,
+        safe: &[TilePos],
+    ) -> Result<(), TooManyMines> {
+        let covered = tiles_in_rect(min, max)
+            .filter(|&pos| matches!(self.get_tile(pos), Tile::Covered(_, _)))
+            .collect_vec();
+        let mut candidates = covered
+            .iter()
+            .copied()
+            .filter(|pos| !safe.contains(pos))
+            .collect_vec();
+        if count > candidates.len() {
+            return Err(TooManyMines(candidates.len()));
+        }
+        for chunk_pos in chunks_in_rect(min, max) {
+            self.place_mines_in_chunk(chunk_pos);
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        if self.arena == Some((min, max)) {
+            self.arena_mine_count = Some(count);
+        }
+        let mines: HashSet<TilePos> = candidates
+            .partial_shuffle(&mut rng, count)
+            .0
+            .iter()
+            .copied()
+            .collect();
+        for pos in covered {
+            if let Tile::Covered(f, _) = self.get_tile(pos) {
+                let h = if mines.contains(&pos) {
+                    HiddenState::Mine
+                } else {
+                    HiddenState::Safe
+                };
+                self.set_tile(pos, Tile::Covered(f, h));
+            }
+        }
+        Ok(())
+    }
+    /// Returns the number of times mines have been placed in a chunk. Chunks
+    /// loaded from a file are not counted.
+    pub fn generated_chunk_count(&self) -> usize {
+        self.generated_chunk_count
+    }
+    /// Makes every covered tile in a chunk safe and prevents mines from being
+    /// placed there, such as for a safe area around the start of a custom map.
+    ///
+    /// This should only be used on chunks that are not adjacent to any
+    /// revealed tiles, since it may change the numbers those should have.
+    ///
+    /// Nothing makes chunks safe yet; this is for custom maps, which will
+    /// choose their own safe areas.
+    #[allow(dead_code)]
+    pub fn set_chunk_safe(&mut self, pos: ChunkPos) {
+        for tile_pos in tiles_in_rect(pos.min_tile(), pos.max_tile()) {
+            if let Tile::Covered(f, _) = self.get_tile(tile_pos) {
+                self.set_tile(tile_pos, Tile::Covered(f, HiddenState::Safe));
+            }
+        }
+        self.get_chunk_mut(pos).all_mines_placed = true;
+    }
+    /// Returns the number of mines in a chunk, or `None` if mines have not
+    /// been placed in it yet. Like `get_tile()`, this never places mines or
+    /// creates chunks, so it can be used while drawing.
+    pub fn chunk_mine_count(&self, pos: ChunkPos) -> Option<u32> {
+        self.get_chunk(pos)
+            .filter(|chunk| chunk.all_mines_placed())
+            .map(Chunk::mine_count)
+    }
+    /// Returns the random number generator used to place mines in a chunk.
+    fn chunk_rng(&self, ChunkPos(chunk_x, chunk_y): ChunkPos) -> StdRng {
+        // Mix the chunk coordinates into the seed using arbitrary large odd
+        // constants.
+        let chunk_seed = self.seed
+            ^ (chunk_x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (chunk_y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        StdRng::seed_from_u64(chunk_seed)
+    }
+
+    /// Copies tiles from another grid into this one, offset by a whole number
+    /// of chunks so that chunks line up. Where both grids have a tile, the one
+    /// from `other` takes precedence unless it is the default (covered,
+    /// unflagged, with no mine placed).
+    ///
+    /// Mines are considered placed in a chunk after merging if no tile in the
+    /// chunk is left without a hidden state.
+    ///
+    /// Nothing merges grids yet; this is for stitching together puzzles drawn
+    /// as text.
+    #[allow(dead_code)]
+    pub fn merge(&mut self, other: &Grid, offset: ChunkPos) {
+        let ChunkPos(dx, dy) = offset;
+        for (&ChunkPos(chunk_x, chunk_y), other_chunk) in &other.chunks {
+            let chunk_pos = ChunkPos(chunk_x + dx, chunk_y + dy);
+            for pos in tiles_in_rect(chunk_pos.min_tile(), chunk_pos.max_tile()) {
+                let tile = other_chunk.get_tile(pos);
+                if tile != Tile::default() {
+                    self.set_tile(pos, tile);
+                }
+            }
+            let chunk = self.get_chunk_mut(chunk_pos);
+            chunk.all_mines_placed = chunk
+                .tiles
+                .iter()
+                .all(|t| !matches!(t.unpack(), Tile::Covered(_, HiddenState::Unknown)));
+        }
+    }
+
+    /// Moves mines out of a set of tiles, so that they are all safe. Each mine
+    /// is moved to a random safe covered tile in the same chunk, so the number
+    /// of mines in each chunk is unchanged if possible.
+    ///
+    /// This should only be used on tiles that are not adjacent to any revealed
+    /// tiles.
+    pub fn relocate_mines(&mut self, tiles: &[TilePos]) {
+        for &pos in tiles {
+            let chunk_pos = pos.chunk();
+            self.place_mines_in_chunk(chunk_pos);
+            let (f, h) = match self.get_tile(pos) {
+                Tile::Covered(f, h) => (f, h),
+                _ => continue,
+            };
+            if h != HiddenState::Mine {
+                continue;
+            }
+            self.set_tile(pos, Tile::Covered(f, HiddenState::Safe));
+
+            let mut candidates = tiles_in_rect(chunk_pos.min_tile(), chunk_pos.max_tile())
+                .filter(|p| !tiles.contains(p))
+                .collect_vec();
+            let start = self.chunk_rng(chunk_pos).gen_range(0..candidates.len());
+            candidates.rotate_left(start);
+            let new_pos = candidates
+                .into_iter()
+                .find(|&p| matches!(self.get_tile(p), Tile::Covered(_, HiddenState::Safe)));
+            if let Some(new_pos) = new_pos {
+                if let Tile::Covered(f, _) = self.get_tile(new_pos) {
+                    self.set_tile(new_pos, Tile::Covered(f, HiddenState::Mine));
+                }
+            }
+        }
+    }
+
+    /// Returns a hash of every tile that is not the default, which is the same
+    /// for any two grids with the same tiles regardless of how they were
+    /// built. The seed is not included.
+    ///
+    /// The hash is stable across platforms and versions of Rust.
+    pub fn content_hash(&self) -> u64 {
+        let mut tiles = self
+            .chunks
+            .iter()
+            .flat_map(|(&chunk_pos, chunk)| {
+                tiles_in_rect(chunk_pos.min_tile(), chunk_pos.max_tile())
+                    .map(move |pos| (pos, chunk.tiles[Chunk::index_of_tile(pos)]))
+            })
+            .filter(|&(_, tile)| tile != PackedTile::default())
+            .collect_vec();
+        tiles.sort_unstable_by_key(|&(TilePos(x, y), _)| (y, x));
+
+        // 64-bit FNV-1a
+        let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+        for (TilePos(x, y), PackedTile(byte)) in tiles {
+            let (x, y) = (x.to_le_bytes(), y.to_le_bytes());
+            for &b in x.iter().chain(&y).chain(&[byte]) {
+                hash ^= b as u64;
+                hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+            }
+        }
+        hash
+    }
+
+    /// Returns `true` if every safe tile in a rectangle, including `min` and
+    /// `max`, is revealed and every mine in it is flagged. A rectangle that
+    /// overlaps a chunk where mines have not been placed yet is never solved,
+    /// so this never places mines or creates chunks.
+    pub fn is_solved_region(&self, min: TilePos, max: TilePos) -> bool {
+        tiles_in_rect(min, max).all(|pos| {
+            matches!(
+                self.get_tile(pos),
+                Tile::Number(_) | Tile::Covered(FlagState::Flag, HiddenState::Mine),
+            )
+        })
+    }
+
+    /// Returns a text drawing of a rectangle of the grid, including `min` and
+    /// `max`, with one line per row from top to bottom. If `show_mines` is
+    /// `true`, then covered unflagged mines are drawn as `*`.
+    ///
+    /// This is the format read by `from_ascii()`.
+    pub fn dump_region(&self, min: TilePos, max: TilePos, show_mines: bool) -> String {
+        let TilePos(x1, y1) = min;
+        let TilePos(x2, y2) = max;
+        let mut ret = String::new();
+        for y in (y1..=y2).rev() {
+            for x in x1..=x2 {
+                match self.get_tile(TilePos(x, y)) {
+                    Tile::Covered(FlagState::None, HiddenState::Mine) if show_mines => {
+                        ret.push('*')
+                    }
+                    tile => ret.push_str(&tile.to_string()),
+                }
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+    /// Returns a grid from a text drawing in the format returned by
+    /// `dump_region()`, with the top left tile at `top_left`.
+    ///
+    /// Covered tiles are unknown, except for `*`, which is a covered mine.
+    pub fn from_ascii(s: &str, top_left: TilePos) -> Result<Self, ParseError> {
+        let mut ret = Self::with_seed(0);
+        for (dy, line) in s.lines().enumerate() {
+            for (dx, c) in line.trim_end_matches('\r').chars().enumerate() {
+                let tile = match c {
+                    '*' => Tile::Covered(FlagState::None, HiddenState::Mine),
+                    c => Tile::from_char(c).ok_or(ParseError::InvalidTile(c))?,
+                };
+                ret.set_tile(top_left.offset(dx as i32, -(dy as i32)), tile);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Returns `true` if any tile in the grid has been revealed.
+    pub fn any_revealed(&self) -> bool {
+        self.chunks.values().any(Chunk::revealed_any)
+    }
+
+    /// Returns the positions of all flagged tiles in a rectangle, including
+    /// `min` and `max`. Tiles in missing chunks are skipped without creating
+    /// the chunks.
+    ///
+    /// Nothing uses this yet; it is for exporting just the player's flags and
+    /// for drawing them on a minimap.
+    #[allow(dead_code)]
+    pub fn flagged_positions_in_rect(&self, min: TilePos, max: TilePos) -> Vec<TilePos> {
+        let (TilePos(x1, y1), TilePos(x2, y2)) = (min, max);
+        let in_rect = |&TilePos(x, y): &TilePos| (x1..=x2).contains(&x) && (y1..=y2).contains(&y);
+        let mut ret = vec![];
+        for chunk_pos in chunks_in_rect(min, max) {
+            if let Some(chunk) = self.get_chunk(chunk_pos) {
+                for pos in tiles_in_rect(chunk_pos.min_tile(), chunk_pos.max_tile()).filter(in_rect)
+                {
+                    if let Tile::Covered(FlagState::Flag, _) = chunk.get_tile(pos) {
+                        ret.push(pos);
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Returns whether the reveal action that opened each tile is tracked.
+    pub fn tracks_opened_by(&self) -> bool {
+        self.opened_by.is_some()
+    }
+    /// Enables or disables tracking which reveal action opened each tile.
+    /// Disabling it discards everything tracked so far.
+    pub fn set_track_opened_by(&mut self, enabled: bool) {
+        if enabled != self.tracks_opened_by() {
+            self.opened_by = if enabled { Some(HashMap::new()) } else { None };
+        }
+    }
+    /// Returns the ID of the reveal action that opened a tile, or `None` if it
+    /// was not opened while tracking was enabled. Every tile opened by a single
+    /// call to `reveal()` (or similar) has the same ID.
+    pub fn opened_by(&self, pos: TilePos) -> Option<u32> {
+        self.opened_by.as_ref()?.get(&pos).copied()
+    }
+    fn record_opened_by(&mut self, outcome: &RevealOutcome) {
+        if let Some(opened_by) = &mut self.opened_by {
+            if outcome.revealed.is_empty() {
+                return;
+            }
+            let id = self.next_action_id;
+            self.next_action_id += 1;
+            for &pos in &outcome.revealed {
+                opened_by.insert(pos, id);
+            }
+        }
     }
 
-    /// Toggles flag on a tile in the grid.
-    pub fn toggle_flag(&mut self, pos: TilePos) {
-        self.set_tile(pos, self.get_tile(pos).toggle_flag());
+    /// Cycles the annotation on a tile in the grid from nothing to a flag,
+    /// then to a question mark if `include_question` is `true`, then back to
+    /// nothing.
+    ///
+    /// This never places mines, so tiles can be flagged ahead of time in chunks
+    /// that have not been generated yet, such as to set up a puzzle. Flags are
+    /// kept when mines are placed later.
+    pub fn cycle_flag(&mut self, pos: TilePos, include_question: bool) {
+        self.set_tile(pos, self.get_tile(pos).cycle_flag(include_question));
     }
 
     /// Reveals a square.
-    pub fn reveal(&mut self, pos: TilePos) {
+    ///
+    /// Revealing a number that is already revealed never changes the number;
+    /// it only reveals adjacent tiles as in `reveal_adjacent_safely()`, doing
+    /// nothing if the number doesn't have the correct number of flags around
+    /// it. Revealing a mine that is already revealed or a wall does nothing.
+    pub fn reveal(&mut self, pos: TilePos, options: RevealOptions) -> RevealOutcome {
+        if !self.in_arena(pos) {
+            return RevealOutcome::default();
+        }
         match self.get_tile(pos) {
-            Tile::Covered(_, _) => self.reveal_hidden(pos),
-            Tile::Number(_) => self.reveal_adjacent_safely(pos),
-            Tile::Mine => (),
+            Tile::Covered(_, _) => self.reveal_hidden(pos, options),
+            Tile::Number(_) => self.reveal_adjacent_safely(pos, options),
+            Tile::Mine => RevealOutcome::default(),
+        }
+    }
+    /// Reveals a square like `reveal()`, but refuses to reveal any mine.
+    ///
+    /// If revealing the square would reveal a mine, the grid is left unchanged
+    /// (except that mines may be placed) and an error is returned instead.
+    pub fn reveal_safe(
+        &mut self,
+        pos: TilePos,
+        options: RevealOptions,
+    ) -> Result<RevealOutcome, WouldHitMine> {
+        let targets = match self.get_tile(pos) {
+            _ if !self.in_arena(pos) => vec![],
+            Tile::Covered(_, _) => vec![pos],
+            Tile::Number(n) if self.count_assumed_mines(pos, options) == n => {
+                self.neighbors_of_type(pos, |t| !Self::is_assumed_mine(t, options))
+            }
+            _ => vec![],
+        };
+        for target in targets {
+            self.place_mines_in_chunk(target.chunk());
+            match self.get_tile(target) {
+                Tile::Covered(FlagState::None, HiddenState::Mine) => {
+                    return Err(WouldHitMine(target));
+                }
+                Tile::Covered(FlagState::Question, HiddenState::Mine)
+                    if options.reveal_question_marked =>
+                {
+                    return Err(WouldHitMine(target));
+                }
+                _ => (),
+            }
         }
+        Ok(self.reveal(pos, options))
     }
-    /// Reveals a hidden tile in the grid.
-    pub fn reveal_hidden(&mut self, pos: TilePos) {
-        self.place_mines_in_chunk(pos.chunk());
+    /// Reveals a hidden tile in the grid. If it has no adjacent mines, its
+    /// neighbors are revealed too, spreading outward until numbered tiles.
+    ///
+    /// Flagged tiles are never revealed, so they block the spread. The same
+    /// goes for question-marked tiles unless `reveal_question_marked` is
+    /// `true` in `options`.
+    pub fn reveal_hidden(&mut self, pos: TilePos, options: RevealOptions) -> RevealOutcome {
+        let mut outcome = RevealOutcome::default();
+        self.reveal_hidden_into(pos, &mut outcome, options);
+        self.record_opened_by(&outcome);
+        outcome
+    }
+    fn reveal_hidden_into(
+        &mut self,
+        pos: TilePos,
+        outcome: &mut RevealOutcome,
+        options: RevealOptions,
+    ) {
+        // Use an explicit stack instead of recursion, since openings can be
+        // very large (e.g., in safe chunks). Neighbors are pushed in reverse so
+        // that tiles are revealed in the same order as a recursive search.
+        let mut stack = vec![pos];
+        while let Some(pos) = stack.pop() {
+            if !self.in_arena(pos) {
+                continue;
+            }
+            self.place_mines_in_chunk(pos.chunk());
 
-        match self.get_tile(pos) {
-            Tile::Covered(FlagState::None, h) | Tile::Covered(FlagState::Question, h) => match h {
-                HiddenState::Unknown => panic!("expected all mines to be placed"),
-                HiddenState::Safe => {
-                    let n = self.count_neighbors(pos, Tile::is_mine);
-                    self.set_tile(pos, Tile::Number(n));
-                    if n == 0 {
-                        for nbr in pos.neighbors() {
-                            self.reveal_hidden(nbr);
+            match self.get_tile(pos) {
+                Tile::Covered(FlagState::Question, _) if !options.reveal_question_marked => (),
+                Tile::Covered(FlagState::None, h) | Tile::Covered(FlagState::Question, h) => {
+                    match h {
+                        HiddenState::Unknown => panic!("expected all mines to be placed"),
+                        HiddenState::Safe => {
+                            let n = self.count_neighbors(pos, Tile::is_mine);
+                            self.set_tile(pos, Tile::Number(n));
+                            outcome.revealed.push(pos);
+                            if n == 0 {
+                                stack.extend(pos.neighbors().collect_vec().into_iter().rev());
+                            }
+                        }
+                        HiddenState::Mine => {
+                            self.set_tile(pos, Tile::Mine);
+                            outcome.revealed.push(pos);
+                            outcome.hit_mine = true;
                         }
                     }
                 }
-                HiddenState::Mine => {
-                    self.set_tile(pos, Tile::Mine);
-                }
-            },
-            _ => (),
+                // Flags are a barrier; the player must unflag them first.
+                Tile::Covered(FlagState::Flag, _) => (),
+                Tile::Number(_) | Tile::Mine => (),
+            }
         }
     }
     /// Reveals hidden tiles adjacent to a known one, if the correct number of
     /// flags have been placed nearby.
-    pub fn reveal_adjacent_safely(&mut self, pos: TilePos) {
-        match self.get_tile(pos) {
-            Tile::Number(n) => {
-                let n_flags = self.count_neighbors(pos, Tile::is_assumed_mine);
-                if n_flags == n {
-                    for nbr in pos.neighbors() {
-                        self.reveal_hidden(nbr);
+    pub fn reveal_adjacent_safely(
+        &mut self,
+        pos: TilePos,
+        options: RevealOptions,
+    ) -> RevealOutcome {
+        let mut outcome = RevealOutcome::default();
+        if let Tile::Number(n) = self.get_tile(pos) {
+            if self.count_assumed_mines(pos, options) == n {
+                for nbr in pos.neighbors() {
+                    if !Self::is_assumed_mine(self.get_tile(nbr), options) {
+                        self.reveal_hidden_into(nbr, &mut outcome, options);
                     }
                 }
             }
-            _ => (),
         }
+        self.record_opened_by(&outcome);
+        outcome
     }
 
-    /// Returns the number of neighboring tiles that satisfy a predicate,
-    /// populating chunks with mines as needed.
-    fn count_neighbors(&mut self, pos: TilePos, mut predicate: impl FnMut(Tile) -> bool) -> u8 {
+    /// Returns `true` if a tile counts as a mine when chording.
+    fn is_assumed_mine(tile: Tile, options: RevealOptions) -> bool {
+        match tile {
+            Tile::Covered(FlagState::Question, _) => options.question_marks_as_flags,
+            _ => tile.is_assumed_mine(),
+        }
+    }
+    /// Returns the number of neighboring tiles that count as mines when
+    /// chording, without placing mines or creating chunks.
+    ///
+    /// This only depends on what the player can see. Using `count_neighbors()`
+    /// instead would needlessly place mines in neighboring chunks even when
+    /// nothing is revealed.
+    ///
+    /// Each tile counts as its weight from `mine_weight()`, like in the number
+    /// it is compared to.
+    pub fn count_assumed_mines(&self, pos: TilePos, options: RevealOptions) -> u8 {
         pos.neighbors()
-            .filter(|&p| {
-                self.place_mines_in_chunk(p.chunk());
-                predicate(self.get_tile(p))
+            .filter(|&nbr| nbr != pos && Self::is_assumed_mine(self.get_tile(nbr), options))
+            .fold(0_u8, |total, nbr| {
+                total.saturating_add(self.mine_weight(nbr))
             })
-            .count() as u8
+    }
+    /// Returns the number of question marks in a summary that count as mines
+    /// when chording.
+    fn question_marks_counted(summary: NeighborSummary, options: RevealOptions) -> u8 {
+        if options.question_marks_as_flags {
+            summary.question_marked
+        } else {
+            0
+        }
+    }
+    /// Returns the number of each kind of tile around a position, not
+    /// including the position itself. Like `get_tile()`, this never places
+    /// mines or creates chunks.
+    ///
+    /// Tiles are counted without their mine weights. Use
+    /// `count_assumed_mines()` to compare mines to a number.
+    pub fn neighbor_summary(&self, pos: TilePos) -> NeighborSummary {
+        let mut ret = NeighborSummary::default();
+        for (i, nbr) in pos.neighbors().filter(|&nbr| nbr != pos).enumerate() {
+            match self.get_tile(nbr) {
+                Tile::Covered(FlagState::Flag, _) => ret.flagged += 1,
+                Tile::Covered(f, _) => {
+                    ret.covered += 1;
+                    ret.covered_mask |= 1 << i;
+                    if f == FlagState::Question {
+                        ret.question_marked += 1;
+                    }
+                }
+                Tile::Mine => ret.revealed_mines += 1,
+                Tile::Number(_) => (),
+            }
+        }
+        ret
+    }
+
+    /// Returns `true` if revealing a number would reveal any adjacent tiles,
+    /// because it has the correct number of flags around it and at least one
+    /// other covered neighbor. This never places mines or creates chunks.
+    pub fn can_chord(&self, pos: TilePos, options: RevealOptions) -> bool {
+        match self.get_tile(pos) {
+            Tile::Number(n) => {
+                let summary = self.neighbor_summary(pos);
+                self.count_assumed_mines(pos, options) == n
+                    && summary.covered > Self::question_marks_counted(summary, options)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the positions of neighboring tiles, not including `pos` itself,
+    /// that satisfy a predicate. Like `get_tile()`, this never places mines
+    /// or creates chunks.
+    pub fn neighbors_of_type(
+        &self,
+        pos: TilePos,
+        mut predicate: impl FnMut(Tile) -> bool,
+    ) -> Vec<TilePos> {
+        pos.neighbors()
+            .filter(|&p| p != pos && predicate(self.get_tile(p)))
+            .collect()
+    }
+    /// Returns the total weight of neighboring tiles that satisfy a
+    /// predicate, populating chunks with mines as needed. Each tile has a
+    /// weight of 1 unless it is given a different one by `set_mine_weight()`.
+    fn count_neighbors(&mut self, pos: TilePos, mut predicate: impl FnMut(Tile) -> bool) -> u8 {
+        let mut total = 0_u8;
+        for p in pos.neighbors() {
+            if !self.in_arena(p) {
+                continue;
+            }
+            self.place_mines_in_chunk(p.chunk());
+            if predicate(self.get_tile(p)) {
+                total = total.saturating_add(self.mine_weight(p));
+            }
+        }
+        total
+    }
+
+    /// Returns how many mines a mine at a position counts as in the numbers
+    /// around it.
+    pub fn mine_weight(&self, pos: TilePos) -> u8 {
+        self.mine_weights.get(&pos).copied().unwrap_or(1)
+    }
+    /// Sets how many mines a mine at a position counts as in the numbers
+    /// around it, for variants where some mines are worth more than others.
+    /// This only affects numbers revealed afterward.
+    ///
+    /// Weights are part of the rules of the variant rather than hidden
+    /// information, so flags and revealed mines count as their weight when
+    /// chording and solving, whether or not the tile is really a mine.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is 0, since the solver assumes that a number with no
+    /// mines left around it has only safe tiles around it.
+    pub fn set_mine_weight(&mut self, pos: TilePos, weight: u8) {
+        assert!(weight > 0, "mine weight must be at least 1");
+        if weight == 1 {
+            self.mine_weights.remove(&pos);
+        } else {
+            self.mine_weights.insert(pos, weight);
+        }
+    }
+}
+
+/// How walls outside the arena appear to anything that reads the grid.
+const WALL_TILE: Tile = Tile::Number(0);
+
+/// Number of each kind of tile around a position.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct NeighborSummary {
+    /// Number of flagged tiles, not including question marks.
+    pub flagged: u8,
+    /// Number of covered tiles that are not flagged, including question marks.
+    pub covered: u8,
+    /// Number of covered tiles with question marks.
+    pub question_marked: u8,
+    /// Number of revealed mines.
+    pub revealed_mines: u8,
+    /// Which neighbors are counted in `covered`, as a bit for each neighbor in
+    /// the order of `TilePos::neighbors()`, skipping the tile itself.
+    pub covered_mask: u8,
+}
+impl NeighborSummary {
+    /// Returns the positions of the neighbors counted in `covered`, given the
+    /// position that the summary is for.
+    pub fn covered_tiles(self, pos: TilePos) -> impl Iterator<Item = TilePos> {
+        pos.neighbors()
+            .filter(move |&nbr| nbr != pos)
+            .enumerate()
+            .filter(move |&(i, _)| self.covered_mask & (1 << i) != 0)
+            .map(|(_, nbr)| nbr)
+    }
+}
+
+/// Rules for revealing tiles that depend on the player's settings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RevealOptions {
+    /// Whether question marks count as flags when chording. If they do, then
+    /// chording never reveals question-marked tiles.
+    pub question_marks_as_flags: bool,
+    /// Whether question-marked tiles can be revealed. If they can't, then they
+    /// block reveals like flags do.
+    pub reveal_question_marked: bool,
+}
+impl Default for RevealOptions {
+    fn default() -> Self {
+        Self {
+            question_marks_as_flags: false,
+            reveal_question_marked: true,
+        }
+    }
+}
+
+/// Result of revealing tiles in the grid.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RevealOutcome {
+    /// Whether a mine was revealed.
+    pub hit_mine: bool,
+    /// Positions of tiles that were revealed, in the order they were revealed.
+    pub revealed: Vec<TilePos>,
+}
+
+/// Error returned when a reveal was refused because it would have revealed the
+/// mine at a position.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WouldHitMine(pub TilePos);
+
+/// Error returned when mines could not be placed because there are more mines
+/// than tiles that can be mines, which is the number given.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TooManyMines(pub usize);
+impl fmt::Display for TooManyMines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Only {} squares can be mines", self.0)
     }
 }
+impl std::error::Error for TooManyMines {}
 
 /// Square chunk of tiles.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Chunk {
     tiles: [PackedTile; CHUNK_SIZE * CHUNK_SIZE],
     all_mines_placed: bool,
+    /// Number of revealed tiles in the chunk.
+    revealed_count: u16,
 }
 impl Default for Chunk {
     fn default() -> Self {
         Self {
             tiles: [PackedTile::default(); CHUNK_SIZE * CHUNK_SIZE],
             all_mines_placed: false,
+            revealed_count: 0,
         }
     }
 }
@@ -192,7 +1087,7 @@ impl fmt::Display for Chunk {
     }
 }
 impl FromStr for Chunk {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut tiles = vec![];
@@ -205,9 +1100,11 @@ impl FromStr for Chunk {
                 _ => tiles.push(PackedTile(ch as u8)),
             }
         }
+        let revealed_count = tiles.iter().filter(|t| t.unpack().is_revealed()).count() as u16;
         Ok(Self {
-            tiles: tiles.try_into().map_err(|_| ())?,
+            tiles: tiles.try_into().map_err(|_| ParseError::InvalidGrid)?,
             all_mines_placed,
+            revealed_count,
         })
     }
 }
@@ -225,13 +1122,61 @@ impl Chunk {
     }
     /// Sets a tile in the chunk.
     pub fn set_tile(&mut self, pos: TilePos, tile: Tile) {
-        self.tiles[Self::index_of_tile(pos)] = tile.pack();
+        let packed = &mut self.tiles[Self::index_of_tile(pos)];
+        if packed.unpack().is_revealed() {
+            self.revealed_count -= 1;
+        }
+        if tile.is_revealed() {
+            self.revealed_count += 1;
+        }
+        *packed = tile.pack();
+    }
+
+    /// Returns the number of revealed tiles in the chunk.
+    pub fn revealed_count(&self) -> usize {
+        self.revealed_count as usize
+    }
+    /// Returns `true` if any tile in the chunk has been revealed.
+    pub fn revealed_any(&self) -> bool {
+        self.revealed_count > 0
+    }
+
+    /// Returns whether mines have been placed in every tile of the chunk.
+    pub fn all_mines_placed(&self) -> bool {
+        self.all_mines_placed
+    }
+
+    /// Returns the number of mines in the chunk, both covered and revealed.
+    /// This is only accurate once all mines have been placed in the chunk.
+    pub fn mine_count(&self) -> u32 {
+        self.tiles.iter().filter(|t| t.unpack().is_mine()).count() as u32
+    }
+    /// Returns the number of flagged tiles in the chunk, not including
+    /// question marks.
+    pub fn flag_count(&self) -> usize {
+        self.tiles
+            .iter()
+            .filter(|t| t.unpack().is_flagged())
+            .count()
     }
 }
 
 /// Tile coordinates.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct TilePos(pub i32, pub i32);
+impl fmt::Display for TilePos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.0, self.1)
+    }
+}
+impl FromStr for TilePos {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = parse_coordinates(s)?;
+        Ok(TilePos(x, y))
+    }
+}
 impl TilePos {
     /// Returns the position of the chunk containing the tile position.
     pub fn chunk(self) -> ChunkPos {
@@ -242,10 +1187,1201 @@ impl TilePos {
     pub fn neighbors(self) -> impl Iterator<Item = Self> {
         (-1..=1)
             .cartesian_product(-1..=1)
-            .map(move |(dx, dy)| TilePos(self.0 + dx, self.1 + dy))
+            .map(move |(dx, dy)| self.offset(dx, dy))
+    }
+    /// Returns the position offset by `dx` tiles horizontally and `dy` tiles
+    /// vertically.
+    pub fn offset(self, dx: i32, dy: i32) -> Self {
+        TilePos(self.0 + dx, self.1 + dy)
+    }
+    /// Returns the number of orthogonal steps between two positions.
+    ///
+    /// Nothing uses this yet; it is for hints that point out the nearest tile
+    /// that can be deduced.
+    #[allow(dead_code)]
+    pub fn manhattan_distance(self, other: Self) -> u64 {
+        self.0.abs_diff(other.0) as u64 + self.1.abs_diff(other.1) as u64
+    }
+    /// Returns the number of steps between two positions, where diagonal steps
+    /// are allowed. Neighboring tiles are at a distance of 1.
+    pub fn chebyshev_distance(self, other: Self) -> u32 {
+        std::cmp::max(self.0.abs_diff(other.0), self.1.abs_diff(other.1))
+    }
+}
+
+/// Simple multiplicative hasher for chunk positions, based on the one used in
+/// `rustc`. It is not resistant to collision attacks, but chunk positions don't
+/// come from untrusted input.
+#[derive(Debug, Default, Copy, Clone)]
+struct ChunkPosHasher(u64);
+impl ChunkPosHasher {
+    const MULTIPLIER: u64 = 0x517c_c1b7_2722_0a95;
+
+    fn add(&mut self, word: u64) {
+        self.0 = (self.0.rotate_left(5) ^ word).wrapping_mul(Self::MULTIPLIER);
+    }
+}
+impl Hasher for ChunkPosHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.add(b as u64);
+        }
+    }
+    fn write_i32(&mut self, i: i32) {
+        self.add(i as u32 as u64);
     }
 }
 
 /// Global coordinates of a chunk.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ChunkPos(pub i32, pub i32);
+impl fmt::Display for ChunkPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.0, self.1)
+    }
+}
+impl FromStr for ChunkPos {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = parse_coordinates(s)?;
+        Ok(ChunkPos(x, y))
+    }
+}
+
+impl ChunkPos {
+    /// Returns the position of the tile in the chunk with the lowest
+    /// coordinates.
+    pub fn min_tile(self) -> TilePos {
+        let ChunkPos(x, y) = self;
+        TilePos(x << CHUNK_SIZE_LOG_2, y << CHUNK_SIZE_LOG_2)
+    }
+    /// Returns the position of the tile in the chunk with the highest
+    /// coordinates.
+    pub fn max_tile(self) -> TilePos {
+        let TilePos(x, y) = self.min_tile();
+        TilePos(x + CHUNK_SIZE as i32 - 1, y + CHUNK_SIZE as i32 - 1)
+    }
+}
+
+/// Returns an iterator over the positions of all tiles in a rectangle,
+/// including `min` and `max`, in row-major order.
+pub fn tiles_in_rect(min: TilePos, max: TilePos) -> impl Iterator<Item = TilePos> {
+    let TilePos(x1, y1) = min;
+    let TilePos(x2, y2) = max;
+    (y1..=y2).flat_map(move |y| (x1..=x2).map(move |x| TilePos(x, y)))
+}
+/// Returns an iterator over the positions of all chunks that overlap a
+/// rectangle of tiles, including `min` and `max`.
+pub fn chunks_in_rect(min: TilePos, max: TilePos) -> impl Iterator<Item = ChunkPos> {
+    let ChunkPos(chunk_x1, chunk_y1) = min.chunk();
+    let ChunkPos(chunk_x2, chunk_y2) = max.chunk();
+    (chunk_y1..=chunk_y2).flat_map(move |y| (chunk_x1..=chunk_x2).map(move |x| ChunkPos(x, y)))
+}
+
+/// Parses a pair of integer coordinates separated by a comma, such as `-3,
+/// 12`.
+fn parse_coordinates(s: &str) -> Result<(i32, i32), ParseError> {
+    let invalid = || ParseError::InvalidCoordinates(s.trim().to_owned());
+    let (x, y) = s.split_once(',').ok_or_else(invalid)?;
+    Ok((
+        x.trim().parse().map_err(|_| invalid())?,
+        y.trim().parse().map_err(|_| invalid())?,
+    ))
+}
+
+#[cfg(test)]
+#[test]
+fn test_reset_flags() {
+    let mut grid = test_grid_with_mines(&[TilePos(1, 0), TilePos(-2, 5)]);
+    grid.cycle_flag(TilePos(1, 0), false);
+    grid.cycle_flag(TilePos(-2, 5), false);
+    grid.cycle_flag(TilePos(7, 7), false);
+    grid.set_tile(
+        TilePos(3, 3),
+        Tile::Covered(FlagState::Question, HiddenState::Safe),
+    );
+    grid.reveal(TilePos(0, 0), RevealOptions::default());
+    grid.reveal(TilePos(0, 1), RevealOptions::default());
+    assert_eq!(grid.flag_count(), 3);
+    assert_eq!(grid.to_string().parse::<Grid>().unwrap().flag_count(), 3);
+
+    grid.reset_flags();
+    assert_eq!(grid.flag_count(), 0);
+    assert_eq!(
+        grid.flagged_positions_in_rect(TilePos(-64, -64), TilePos(63, 63)),
+        vec![]
+    );
+    assert_eq!(
+        grid.get_tile(TilePos(3, 3)),
+        Tile::Covered(FlagState::None, HiddenState::Safe),
+    );
+    assert_eq!(
+        grid.get_tile(TilePos(1, 0)),
+        Tile::Covered(FlagState::None, HiddenState::Mine),
+    );
+    assert_eq!(grid.get_tile(TilePos(0, 0)), Tile::Number(1));
+    assert_eq!(grid.get_tile(TilePos(0, 1)), Tile::Number(1));
+}
+
+/// Returns a grid with mines placed in all chunks near the origin, where the
+/// only mines are those in `mines`.
+#[cfg(test)]
+pub(super) fn test_grid_with_mines(mines: &[TilePos]) -> Grid {
+    let mut grid = Grid::with_seed(0);
+    for chunk_y in -1..=1 {
+        for chunk_x in -1..=1 {
+            let chunk = grid.get_chunk_mut(ChunkPos(chunk_x, chunk_y));
+            for tile in &mut chunk.tiles {
+                *tile = Tile::Covered(FlagState::None, HiddenState::Safe).pack();
+            }
+            chunk.all_mines_placed = true;
+        }
+    }
+    for &pos in mines {
+        grid.set_tile(pos, Tile::Covered(FlagState::None, HiddenState::Mine));
+    }
+    grid
+}
+
+/// Returns the positions of the border of the square from `(x1, y1)` to `(x2,
+/// y2)`, inclusive.
+#[cfg(test)]
+pub(super) fn test_square_border(
+    TilePos(x1, y1): TilePos,
+    TilePos(x2, y2): TilePos,
+) -> Vec<TilePos> {
+    (x1..=x2)
+        .cartesian_product(y1..=y2)
+        .filter(|&(x, y)| x == x1 || x == x2 || y == y1 || y == y2)
+        .map(|(x, y)| TilePos(x, y))
+        .collect()
+}
+
+#[cfg(test)]
+#[test]
+fn test_get_tile_without_chunks() {
+    let grid = Grid::with_seed(0);
+    for pos in tiles_in_rect(TilePos(-100, -100), TilePos(100, 100)) {
+        assert_eq!(grid.get_tile(pos), Tile::default());
+    }
+    assert_eq!(grid.chunk_count(), 0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_changed_tiles() {
+    let mut grid = Grid::with_seed(0);
+    let flag = Tile::Covered(FlagState::Flag, HiddenState::Unknown);
+    grid.set_tile(TilePos(0, 0), flag);
+    grid.set_track_changes(true);
+    assert!(grid.take_changed_tiles().is_empty());
+
+    grid.set_tile(TilePos(1, 2), Tile::Number(3));
+    grid.set_tile(TilePos(-100, 5), flag);
+    grid.set_tile(TilePos(1, 2), Tile::Number(4));
+    // Setting a tile to the same value is not a change.
+    grid.set_tile(TilePos(7, 7), Tile::default());
+    grid.set_tile(TilePos(0, 0), flag);
+    assert_eq!(
+        grid.take_changed_tiles(),
+        [TilePos(1, 2), TilePos(-100, 5)].iter().copied().collect(),
+    );
+    assert!(grid.take_changed_tiles().is_empty());
+
+    grid.reset_flags();
+    assert_eq!(
+        grid.take_changed_tiles(),
+        [TilePos(0, 0), TilePos(-100, 5)].iter().copied().collect(),
+    );
+
+    grid.set_track_changes(false);
+    grid.set_tile(TilePos(3, 3), Tile::Mine);
+    assert!(grid.take_changed_tiles().is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn test_neighbors_of_type() {
+    let mut grid = Grid::from_ascii("#F#\n1 #\n??!", TilePos(0, 2)).unwrap();
+    let is_covered = |tile| matches!(tile, Tile::Covered(_, _));
+    assert_eq!(
+        grid.neighbors_of_type(TilePos(1, 1), is_covered),
+        [
+            TilePos(0, 0),
+            TilePos(0, 2),
+            TilePos(1, 0),
+            TilePos(1, 2),
+            TilePos(2, 1),
+            TilePos(2, 2),
+        ],
+    );
+    assert_eq!(
+        grid.neighbors_of_type(TilePos(1, 1), Tile::is_flagged),
+        [TilePos(1, 2)],
+    );
+    // The tile itself is not included.
+    grid.set_tile(TilePos(1, 1), Tile::default());
+    assert!(!grid
+        .neighbors_of_type(TilePos(1, 1), is_covered)
+        .contains(&TilePos(1, 1)));
+    // Missing chunks are covered, but not created.
+    assert_eq!(grid.neighbors_of_type(TilePos(-64, 0), is_covered).len(), 8);
+    assert_eq!(grid.chunk_count(), 1);
+}
+
+#[cfg(test)]
+#[test]
+fn test_neighbor_summary() {
+    let grid = Grid::from_ascii("F?#\n!3*\n1  \n", TilePos(-1, 1)).unwrap();
+    let chunk_count = grid.chunk_count();
+    assert_eq!(
+        grid.neighbor_summary(TilePos(0, 0)),
+        NeighborSummary {
+            flagged: 1,
+            covered: 3,
+            question_marked: 1,
+            revealed_mines: 1,
+            covered_mask: 0b1101_0000,
+        },
+    );
+    assert_eq!(
+        grid.neighbor_summary(TilePos(0, 0))
+            .covered_tiles(TilePos(0, 0))
+            .collect_vec(),
+        [TilePos(0, 1), TilePos(1, 0), TilePos(1, 1)],
+    );
+    // The tile itself is not included.
+    assert_eq!(
+        grid.neighbor_summary(TilePos(-1, 0)),
+        NeighborSummary {
+            flagged: 1,
+            covered: 4,
+            question_marked: 1,
+            revealed_mines: 0,
+            covered_mask: 0b1000_0111,
+        },
+    );
+    // Tiles outside the drawing are covered, but they are not created.
+    assert_eq!(grid.neighbor_summary(TilePos(1, -1)).covered, 6);
+    assert_eq!(grid.neighbor_summary(TilePos(100, 100)).covered, 8);
+    assert_eq!(grid.chunk_count(), chunk_count);
+}
+
+#[cfg(test)]
+#[test]
+fn test_reveal_outcome() {
+    // Surround a 3x3 region with mines.
+    let mut grid = test_grid_with_mines(&test_square_border(TilePos(0, 0), TilePos(4, 4)));
+
+    let outcome = grid.reveal(TilePos(2, 2), RevealOptions::default());
+    assert!(!outcome.hit_mine);
+    let mut revealed = outcome.revealed;
+    revealed.sort_by_key(|&TilePos(x, y)| (x, y));
+    let expected = (1..=3)
+        .cartesian_product(1..=3)
+        .map(|(x, y)| TilePos(x, y))
+        .collect_vec();
+    assert_eq!(revealed, expected);
+
+    // Revealing an already-revealed number with no flags does nothing.
+    assert_eq!(
+        grid.reveal(TilePos(1, 1), RevealOptions::default()),
+        RevealOutcome::default()
+    );
+
+    let outcome = grid.reveal(TilePos(0, 0), RevealOptions::default());
+    assert!(outcome.hit_mine);
+    assert_eq!(outcome.revealed, vec![TilePos(0, 0)]);
+    assert_eq!(grid.get_tile(TilePos(0, 0)), Tile::Mine);
+}
+
+#[cfg(test)]
+#[test]
+fn test_reveal_idempotent() {
+    // A 5x5 region surrounded by mines, with one more mine inside. Opening
+    // the region leaves three safe tiles in the corner covered.
+    let mut mines = test_square_border(TilePos(0, 0), TilePos(6, 6));
+    mines.push(TilePos(2, 2));
+    let mut grid = test_grid_with_mines(&mines);
+    grid.reveal(TilePos(4, 4), RevealOptions::default());
+    assert!(matches!(grid.get_tile(TilePos(2, 1)), Tile::Covered(_, _)));
+    let number_pos = TilePos(3, 1);
+    assert_eq!(grid.get_tile(number_pos), Tile::Number(4));
+
+    // Revealing an unsatisfied number does nothing.
+    let hash = grid.content_hash();
+    assert_eq!(
+        grid.reveal(number_pos, RevealOptions::default()),
+        RevealOutcome::default()
+    );
+    assert_eq!(grid.content_hash(), hash);
+
+    // The number is never recounted, even if it is wrong.
+    grid.set_tile(number_pos, Tile::Number(7));
+    assert_eq!(
+        grid.reveal(number_pos, RevealOptions::default()),
+        RevealOutcome::default()
+    );
+    assert_eq!(grid.get_tile(number_pos), Tile::Number(7));
+    grid.set_tile(number_pos, Tile::Number(4));
+
+    // Revealing a satisfied number only reveals the tiles around it.
+    for &pos in &[TilePos(2, 0), TilePos(3, 0), TilePos(4, 0), TilePos(2, 2)] {
+        grid.cycle_flag(pos, false);
+    }
+    let outcome = grid.reveal(number_pos, RevealOptions::default());
+    assert_eq!(outcome.revealed, vec![TilePos(2, 1)]);
+    assert_eq!(grid.get_tile(number_pos), Tile::Number(4));
+    let hash = grid.content_hash();
+    assert_eq!(
+        grid.reveal(number_pos, RevealOptions::default()),
+        RevealOutcome::default()
+    );
+    assert_eq!(grid.content_hash(), hash);
+
+    // Revealing a revealed mine does nothing.
+    assert!(
+        grid.reveal(TilePos(0, 6), RevealOptions::default())
+            .hit_mine
+    );
+    let hash = grid.content_hash();
+    assert_eq!(
+        grid.reveal(TilePos(0, 6), RevealOptions::default()),
+        RevealOutcome::default()
+    );
+    assert_eq!(grid.get_tile(TilePos(0, 6)), Tile::Mine);
+    assert_eq!(grid.content_hash(), hash);
+}
+
+#[cfg(test)]
+#[test]
+fn test_flags_block_reveal() {
+    let mut grid = test_grid_with_mines(&test_square_border(TilePos(0, 0), TilePos(6, 6)));
+
+    // Split the open region with a wall of flags.
+    for y in 1..=5 {
+        grid.cycle_flag(TilePos(3, y), false);
+    }
+    let outcome = grid.reveal(TilePos(2, 3), RevealOptions::default());
+    assert_eq!(outcome.revealed.len(), 10);
+    for y in 1..=5 {
+        assert!(matches!(grid.get_tile(TilePos(2, y)), Tile::Number(_)));
+        assert_eq!(
+            grid.get_tile(TilePos(3, y)),
+            Tile::Covered(FlagState::Flag, HiddenState::Safe),
+        );
+        assert_eq!(
+            grid.get_tile(TilePos(4, y)),
+            Tile::Covered(FlagState::None, HiddenState::Safe),
+        );
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_opened_by() {
+    let mut grid = test_grid_with_mines(&test_square_border(TilePos(0, 0), TilePos(4, 4)));
+    grid.reveal(TilePos(1, 1), RevealOptions::default());
+    assert_eq!(grid.opened_by(TilePos(1, 1)), None);
+
+    grid.set_track_opened_by(true);
+    let first = grid
+        .reveal(TilePos(2, 2), RevealOptions::default())
+        .revealed;
+    assert_eq!(first.len(), 8);
+    let id = grid.opened_by(TilePos(2, 2)).unwrap();
+    for pos in first {
+        assert_eq!(grid.opened_by(pos), Some(id));
+    }
+    // The tile revealed before tracking started is not tagged.
+    assert_eq!(grid.opened_by(TilePos(1, 1)), None);
+
+    // Revealing a mine is a separate action.
+    grid.reveal(TilePos(0, 0), RevealOptions::default());
+    assert_ne!(grid.opened_by(TilePos(0, 0)), Some(id));
+    assert!(grid.opened_by(TilePos(0, 0)).is_some());
+
+    grid.set_track_opened_by(false);
+    assert_eq!(grid.opened_by(TilePos(2, 2)), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_is_solved_region() {
+    let mut grid = test_grid_with_mines(&test_square_border(TilePos(0, 0), TilePos(4, 4)));
+    grid.reveal(TilePos(2, 2), RevealOptions::default());
+    assert!(grid.is_solved_region(TilePos(1, 1), TilePos(3, 3)));
+    assert!(!grid.is_solved_region(TilePos(0, 0), TilePos(3, 3)));
+    for pos in test_square_border(TilePos(0, 0), TilePos(4, 4)) {
+        grid.cycle_flag(pos, false);
+    }
+    assert!(grid.is_solved_region(TilePos(0, 0), TilePos(4, 4)));
+    assert!(!grid.is_solved_region(TilePos(0, 0), TilePos(5, 4)));
+
+    // A misplaced flag doesn't count.
+    grid.cycle_flag(TilePos(0, 0), false);
+    grid.cycle_flag(TilePos(5, 0), false);
+    assert!(!grid.is_solved_region(TilePos(0, 0), TilePos(4, 4)));
+
+    // Chunks that don't exist yet are not solved, and are not created.
+    let grid = Grid::with_seed(0);
+    assert!(!grid.is_solved_region(TilePos(100, 100), TilePos(110, 110)));
+    assert!(grid.get_chunk(TilePos(100, 100).chunk()).is_none());
+}
+
+#[cfg(test)]
+#[test]
+fn test_chord_does_not_generate_chunks() {
+    let mut grid = Grid::with_seed(0);
+    // Number on the edge of a chunk, missing a flag.
+    grid.set_tile(TilePos(63, 10), Tile::Number(2));
+    grid.cycle_flag(TilePos(62, 10), false);
+    assert_eq!(grid.chunk_count(), 1);
+
+    assert_eq!(
+        grid.reveal(TilePos(63, 10), RevealOptions::default()),
+        RevealOutcome::default()
+    );
+    assert_eq!(
+        grid.reveal_safe(TilePos(63, 10), RevealOptions::default()),
+        Ok(RevealOutcome::default())
+    );
+    assert_eq!(grid.chunk_count(), 1);
+    assert_eq!(grid.generated_chunk_count(), 0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_reveal_question_marked() {
+    let mut grid = test_grid_with_mines(&test_square_border(TilePos(0, 0), TilePos(6, 6)));
+    let question = Tile::Covered(FlagState::Question, HiddenState::Safe);
+    grid.set_tile(TilePos(3, 3), question);
+
+    // By default, question marks can be revealed like any other tile.
+    let mut default_grid = grid.clone();
+    assert!(RevealOptions::default().reveal_question_marked);
+    assert!(!default_grid
+        .reveal(TilePos(3, 3), RevealOptions::default())
+        .revealed
+        .is_empty());
+    assert_eq!(default_grid.get_tile(TilePos(3, 3)), Tile::Number(0));
+
+    // Otherwise, they are left covered, and block the spread of an opening.
+    let options = RevealOptions {
+        reveal_question_marked: false,
+        ..RevealOptions::default()
+    };
+    assert_eq!(
+        grid.reveal(TilePos(3, 3), options),
+        RevealOutcome::default()
+    );
+    assert_eq!(grid.get_tile(TilePos(3, 3)), question);
+    let outcome = grid.reveal(TilePos(2, 2), options);
+    assert_eq!(outcome.revealed.len(), 24);
+    assert_eq!(grid.get_tile(TilePos(3, 3)), question);
+
+    // A question-marked mine is not revealed either.
+    grid.set_tile(
+        TilePos(0, 0),
+        Tile::Covered(FlagState::Question, HiddenState::Mine),
+    );
+    assert!(!grid.reveal(TilePos(0, 0), options).hit_mine);
+    assert!(grid.reveal_safe(TilePos(0, 0), options).is_ok());
+}
+
+#[cfg(test)]
+#[test]
+fn test_question_marks_as_flags() {
+    let mut mines = test_square_border(TilePos(-1, -1), TilePos(3, 3));
+    mines.push(TilePos(0, 0));
+    let mut grid = test_grid_with_mines(&mines);
+    grid.set_tile(TilePos(1, 1), Tile::Number(1));
+    grid.set_tile(
+        TilePos(0, 0),
+        Tile::Covered(FlagState::Question, HiddenState::Mine),
+    );
+
+    // By default, question marks are not flags, so chording does nothing.
+    let mut default_grid = grid.clone();
+    assert_eq!(
+        default_grid.count_assumed_mines(TilePos(1, 1), RevealOptions::default()),
+        0
+    );
+    assert_eq!(
+        default_grid.reveal(TilePos(1, 1), RevealOptions::default()),
+        RevealOutcome::default()
+    );
+
+    // Otherwise, chording reveals everything but the question mark.
+    let options = RevealOptions {
+        question_marks_as_flags: true,
+        ..RevealOptions::default()
+    };
+    assert_eq!(grid.count_assumed_mines(TilePos(1, 1), options), 1);
+    assert_eq!(
+        grid.clone()
+            .reveal_safe(TilePos(1, 1), options)
+            .map(|o| o.hit_mine),
+        Ok(false),
+    );
+    let outcome = grid.reveal(TilePos(1, 1), options);
+    assert!(!outcome.hit_mine);
+    assert_eq!(outcome.revealed.len(), 7);
+    assert_eq!(
+        grid.get_tile(TilePos(0, 0)),
+        Tile::Covered(FlagState::Question, HiddenState::Mine),
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_reveal_safe() {
+    let mut grid = test_grid_with_mines(&test_square_border(TilePos(0, 0), TilePos(4, 4)));
+
+    assert_eq!(
+        grid.reveal_safe(TilePos(0, 0), RevealOptions::default()),
+        Err(WouldHitMine(TilePos(0, 0)))
+    );
+    assert_eq!(
+        grid.get_tile(TilePos(0, 0)),
+        Tile::Covered(FlagState::None, HiddenState::Mine),
+    );
+
+    // Misplace a flag on a safe tile.
+    grid.cycle_flag(TilePos(3, 3), false);
+    let outcome = grid
+        .reveal_safe(TilePos(2, 2), RevealOptions::default())
+        .unwrap();
+    assert_eq!(outcome.revealed.len(), 8);
+
+    // Chording next to the misplaced flag is refused.
+    grid.cycle_flag(TilePos(4, 1), false);
+    grid.cycle_flag(TilePos(4, 2), false);
+    assert_eq!(grid.get_tile(TilePos(3, 2)), Tile::Number(3));
+    assert_eq!(
+        grid.reveal_safe(TilePos(3, 2), RevealOptions::default()),
+        Err(WouldHitMine(TilePos(4, 3)))
+    );
+    assert_eq!(
+        grid.get_tile(TilePos(4, 3)),
+        Tile::Covered(FlagState::None, HiddenState::Mine),
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_tile_pos_distance() {
+    let a = TilePos(-3, 4);
+    let b = TilePos(2, -8);
+    assert_eq!(a.manhattan_distance(b), 17);
+    assert_eq!(b.manhattan_distance(a), 17);
+    assert_eq!(a.chebyshev_distance(b), 12);
+    assert_eq!(a.manhattan_distance(a), 0);
+    assert_eq!(a.chebyshev_distance(a), 0);
+    for nbr in a.neighbors().filter(|&nbr| nbr != a) {
+        assert_eq!(a.chebyshev_distance(nbr), 1);
+    }
+
+    let far = (TilePos(i32::MIN, i32::MIN), TilePos(i32::MAX, i32::MAX));
+    assert_eq!(far.0.chebyshev_distance(far.1), u32::MAX);
+    assert_eq!(far.0.manhattan_distance(far.1), 2 * u32::MAX as u64);
+
+    assert_eq!(a.offset(5, -12), b);
+    assert_eq!(b.offset(-5, 12), a);
+    assert_eq!(a.offset(0, 0), a);
+}
+
+#[cfg(test)]
+#[test]
+fn test_ascii_round_trip() {
+    let mut grid = test_grid_with_mines(&test_square_border(TilePos(0, 0), TilePos(4, 4)));
+    grid.reveal(TilePos(2, 2), RevealOptions::default());
+    grid.cycle_flag(TilePos(0, 0), false);
+    grid.set_tile(TilePos(4, 0), Tile::Mine);
+    grid.set_tile(
+        TilePos(4, 4),
+        Tile::Covered(FlagState::Question, HiddenState::Mine),
+    );
+
+    let (min, max) = (TilePos(-1, -1), TilePos(4, 4));
+    let dump = grid.dump_region(min, max, false);
+    assert_eq!(dump, "#####?\n##535#\n##3 3#\n##535#\n#F###!\n######\n");
+    let debug_dump = grid.dump_region(min, max, true);
+    assert_eq!(
+        debug_dump,
+        "#****?\n#*535*\n#*3 3*\n#*535*\n#F***!\n######\n",
+    );
+
+    let top_left = TilePos(-1, 4);
+    let loaded = Grid::from_ascii(&debug_dump, top_left).unwrap();
+    assert_eq!(loaded.dump_region(min, max, true), debug_dump);
+    assert_eq!(loaded.dump_region(min, max, false), dump);
+    let loaded = Grid::from_ascii(&dump, top_left).unwrap();
+    assert_eq!(loaded.dump_region(min, max, false), dump);
+
+    // Numbers that cannot occur in a normal game also round-trip.
+    let mut grid = Grid::with_seed(0);
+    grid.set_tile(TilePos(0, 0), Tile::Number(12));
+    let dump = grid.dump_region(TilePos(0, 0), TilePos(1, 0), false);
+    let loaded = Grid::from_ascii(&dump, TilePos(0, 0)).unwrap();
+    assert_eq!(loaded.get_tile(TilePos(0, 0)), Tile::Number(12));
+
+    assert_eq!(
+        Grid::from_ascii("##\n#x", top_left),
+        Err(ParseError::InvalidTile('x')),
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_pos_string_round_trip() {
+    for &(x, y) in &[(0, 0), (-1, 0), (0, -1), (12, -345), (i32::MIN, i32::MAX)] {
+        assert_eq!(TilePos(x, y).to_string().parse(), Ok(TilePos(x, y)));
+        assert_eq!(ChunkPos(x, y).to_string().parse(), Ok(ChunkPos(x, y)));
+    }
+    assert_eq!(TilePos(-3, 12).to_string(), "-3,12");
+    assert_eq!(" -3 ,\t12 ".parse(), Ok(TilePos(-3, 12)));
+
+    for s in &["", "1", "1,", ",1", "1,2,3", "a,b", "1.5,2", "- 1,2"] {
+        let err = ParseError::InvalidCoordinates(s.trim().to_owned());
+        assert_eq!(s.parse::<TilePos>(), Err(err.clone()));
+        assert_eq!(s.parse::<ChunkPos>(), Err(err));
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_content_hash() {
+    let mut a = Grid::with_seed(1);
+    a.set_tile(TilePos(3, -70), Tile::Number(2));
+    a.set_tile(TilePos(100, 5), Tile::Mine);
+    a.cycle_flag(TilePos(-1, -1), false);
+
+    // Build the same tiles in a different order, with an extra empty chunk.
+    let mut b = Grid::with_seed(2);
+    b.cycle_flag(TilePos(-1, -1), false);
+    b.get_chunk_mut(ChunkPos(7, 7));
+    b.set_tile(TilePos(100, 5), Tile::Mine);
+    b.set_tile(TilePos(3, -70), Tile::Number(2));
+    assert_eq!(a.content_hash(), b.content_hash());
+
+    b.set_tile(TilePos(3, -70), Tile::Number(3));
+    assert_ne!(a.content_hash(), b.content_hash());
+    b.set_tile(TilePos(3, -70), Tile::Number(2));
+    b.set_tile(TilePos(4, -70), Tile::Number(0));
+    assert_ne!(a.content_hash(), b.content_hash());
+
+    assert_eq!(
+        Grid::with_seed(1).content_hash(),
+        Grid::with_seed(2).content_hash()
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_set_chunk_safe() {
+    let mut grid = Grid::with_seed(0);
+    grid.set_chunk_safe(ChunkPos(0, 0));
+    assert_eq!(grid.chunk_mine_count(ChunkPos(0, 0)), Some(0));
+
+    // The whole chunk opens at once.
+    let outcome = grid.reveal(TilePos(10, 20), RevealOptions::default());
+    assert!(!outcome.hit_mine);
+    for pos in tiles_in_rect(TilePos(0, 0), TilePos(63, 63)) {
+        let n = pos
+            .neighbors()
+            .filter(|&nbr| grid.get_tile(nbr).is_mine())
+            .count();
+        assert_eq!(grid.get_tile(pos), Tile::Number(n as u8));
+    }
+    // Neighboring chunks still have mines.
+    assert!(grid.chunk_mine_count(ChunkPos(1, 0)).unwrap() > 0);
+
+    // Safe chunks are saved.
+    let mut grid = Grid::with_seed(0);
+    grid.set_chunk_safe(ChunkPos(-3, 2));
+    let loaded: Grid = grid.to_string().parse().unwrap();
+    assert_eq!(loaded.chunk_mine_count(ChunkPos(-3, 2)), Some(0));
+}
+
+#[cfg(test)]
+#[test]
+fn test_chunk_mine_count() {
+    let tile_count = (CHUNK_SIZE * CHUNK_SIZE) as f64;
+    let expected = tile_count * MINE_DENSITY;
+    let std_dev = (tile_count * MINE_DENSITY * (1.0 - MINE_DENSITY)).sqrt();
+
+    let mut grid = Grid::with_seed(42);
+    for &(x, y) in &[(0, 0), (-1, 3), (100, -7)] {
+        let pos = ChunkPos(x, y);
+        // Counting mines never places them.
+        assert_eq!(grid.chunk_mine_count(pos), None);
+        assert!(grid.get_chunk(pos).is_none());
+        grid.place_mines_in_chunk(pos);
+        let count = grid.chunk_mine_count(pos).unwrap() as f64;
+        assert!((count - expected).abs() < 5.0 * std_dev, "{} mines", count);
+    }
+
+    let mut grid = test_grid_with_mines(&[TilePos(0, 0), TilePos(1, 0), TilePos(-1, 0)]);
+    grid.reveal(TilePos(0, 0), RevealOptions::default());
+    grid.place_mines_in_chunk(ChunkPos(1, 1));
+    assert_eq!(grid.chunk_mine_count(ChunkPos(0, 0)), Some(2));
+    assert_eq!(grid.chunk_mine_count(ChunkPos(-1, 0)), Some(1));
+    assert_eq!(grid.chunk_mine_count(ChunkPos(1, 1)), Some(0));
+}
+
+#[cfg(test)]
+#[test]
+fn test_merge() {
+    let safe = Tile::Covered(FlagState::None, HiddenState::Safe);
+    let flag = Tile::Covered(FlagState::Flag, HiddenState::Unknown);
+
+    let mut grid = Grid::with_seed(0);
+    grid.set_tile(TilePos(65, -62), Tile::Number(1));
+    grid.set_tile(TilePos(66, -62), Tile::Number(2));
+    grid.place_mines_in_chunk(ChunkPos(1, -1));
+    grid.place_mines_in_chunk(ChunkPos(0, 0));
+
+    let mut other = test_grid_with_mines(&[TilePos(0, 0)]);
+    other.set_tile(TilePos(1, 2), Tile::Number(3));
+    other.set_tile(TilePos(0, -1), flag);
+
+    grid.merge(&other, ChunkPos(1, -1));
+    // Tiles from `other` take precedence.
+    assert_eq!(grid.get_tile(TilePos(65, -62)), Tile::Number(3));
+    assert_eq!(grid.get_tile(TilePos(66, -62)), safe);
+    assert_eq!(
+        grid.get_tile(TilePos(64, -64)),
+        Tile::Covered(FlagState::None, HiddenState::Mine),
+    );
+    assert_eq!(grid.get_tile(TilePos(64, -65)), flag);
+    assert_eq!(grid.flag_count(), 1);
+    // Chunks are only considered populated if every tile has a hidden state.
+    assert!(grid.get_chunk(ChunkPos(1, -1)).unwrap().all_mines_placed);
+    assert!(!grid.get_chunk(ChunkPos(1, -2)).unwrap().all_mines_placed);
+    // Chunks outside the merged region are unchanged.
+    assert!(grid.get_chunk(ChunkPos(0, 0)).unwrap().all_mines_placed);
+    assert!(grid.get_chunk(ChunkPos(-1, 0)).is_none());
+}
+
+#[cfg(test)]
+#[test]
+fn test_flagged_positions_in_rect() {
+    let mut grid = Grid::with_seed(0);
+    let flags = [
+        TilePos(-65, -1),
+        TilePos(-64, 0),
+        TilePos(0, 0),
+        TilePos(63, 64),
+        TilePos(100, -30),
+        TilePos(200, 200),
+    ];
+    for &pos in &flags {
+        grid.cycle_flag(pos, false);
+    }
+    // Question marks don't count.
+    grid.set_tile(
+        TilePos(5, 5),
+        Tile::Covered(FlagState::Question, HiddenState::Unknown),
+    );
+
+    let mut found = grid.flagged_positions_in_rect(TilePos(-64, -30), TilePos(100, 64));
+    found.sort_by_key(|&TilePos(x, y)| (x, y));
+    assert_eq!(found, flags[1..5]);
+
+    assert_eq!(
+        grid.flagged_positions_in_rect(TilePos(1, 1), TilePos(4, 4)),
+        vec![]
+    );
+    assert_eq!(
+        grid.flagged_positions_in_rect(TilePos(1, 1), TilePos(0, 0)),
+        vec![]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_cycle_flag() {
+    let mut grid = Grid::with_seed(0);
+    let pos = TilePos(2, 3);
+    let flag_state = |grid: &Grid| match grid.get_tile(pos) {
+        Tile::Covered(f, _) => f,
+        other => panic!("tile is not covered: {:?}", other),
+    };
+
+    // Without question marks, flags toggle on and off.
+    grid.cycle_flag(pos, false);
+    assert_eq!(flag_state(&grid), FlagState::Flag);
+    assert_eq!(grid.flag_count(), 1);
+    grid.cycle_flag(pos, false);
+    assert_eq!(flag_state(&grid), FlagState::None);
+    assert_eq!(grid.flag_count(), 0);
+
+    // With question marks, flags turn into question marks first.
+    grid.cycle_flag(pos, true);
+    assert_eq!(flag_state(&grid), FlagState::Flag);
+    assert_eq!(grid.flag_count(), 1);
+    grid.cycle_flag(pos, true);
+    assert_eq!(flag_state(&grid), FlagState::Question);
+    assert_eq!(grid.flag_count(), 0);
+    grid.cycle_flag(pos, true);
+    assert_eq!(flag_state(&grid), FlagState::None);
+    assert_eq!(grid.flag_count(), 0);
+
+    // Question marks are removed even if question marks are disabled.
+    grid.cycle_flag(pos, true);
+    grid.cycle_flag(pos, true);
+    grid.cycle_flag(pos, false);
+    assert_eq!(flag_state(&grid), FlagState::None);
+
+    // Revealed tiles are unaffected.
+    grid.set_tile(pos, Tile::Number(4));
+    grid.cycle_flag(pos, true);
+    assert_eq!(grid.get_tile(pos), Tile::Number(4));
+    assert_eq!(grid.flag_count(), 0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_chunk_map() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut chunk_map = ChunkMap::default();
+    let mut std_map = HashMap::new();
+    for i in 0..10_000 {
+        let pos = ChunkPos(rng.gen_range(-50..50), rng.gen_range(-50..50));
+        let mut chunk = Chunk::default();
+        chunk.set_tile(TilePos(i % 64, 0), Tile::Number(1));
+        assert_eq!(
+            chunk_map.insert(pos, chunk.clone()),
+            std_map.insert(pos, chunk)
+        );
+    }
+    assert_eq!(chunk_map.len(), std_map.len());
+    for x in -60..60 {
+        for y in -60..60 {
+            let pos = ChunkPos(x, y);
+            assert_eq!(chunk_map.get(&pos), std_map.get(&pos));
+        }
+    }
+
+    // Nearby chunks have different hashes.
+    let hashes: HashSet<u64> = tiles_in_rect(TilePos(-8, -8), TilePos(7, 7))
+        .map(|TilePos(x, y)| {
+            let mut hasher = ChunkPosHasher::default();
+            std::hash::Hash::hash(&ChunkPos(x, y), &mut hasher);
+            hasher.finish()
+        })
+        .collect();
+    assert_eq!(hashes.len(), 16 * 16);
+}
+
+/// Compares the speed of looking up chunks in a `ChunkMap` and in a `HashMap`
+/// with the default hasher.
+#[cfg(test)]
+#[test]
+#[ignore]
+fn bench_chunk_map() {
+    use std::hash::BuildHasher;
+    use std::time::{Duration, Instant};
+
+    /// Looks up each chunk many times, like rendering does for every tile.
+    fn time_lookups<S: BuildHasher>(
+        map: &HashMap<ChunkPos, Chunk, S>,
+        positions: &[ChunkPos],
+    ) -> Duration {
+        let start = Instant::now();
+        let mut total = 0;
+        for _ in 0..100 {
+            for pos in positions {
+                for _ in 0..CHUNK_SIZE {
+                    total += map[std::hint::black_box(pos)].all_mines_placed as usize;
+                }
+            }
+        }
+        assert_eq!(total, 0);
+        start.elapsed()
+    }
+
+    let positions: Vec<ChunkPos> = tiles_in_rect(TilePos(-16, -16), TilePos(16, 16))
+        .map(|TilePos(x, y)| ChunkPos(x, y))
+        .collect();
+    let mut chunk_map = ChunkMap::default();
+    let mut std_map = HashMap::new();
+    for &pos in &positions {
+        chunk_map.insert(pos, Chunk::default());
+        std_map.insert(pos, Chunk::default());
+    }
+
+    println!("ChunkMap: {:?}", time_lookups(&chunk_map, &positions));
+    println!("HashMap:  {:?}", time_lookups(&std_map, &positions));
+}
+
+#[cfg(test)]
+#[test]
+fn test_chunk_revealed_count() {
+    let mut grid = test_grid_with_mines(&test_square_border(TilePos(0, 0), TilePos(4, 4)));
+    let chunk_pos = ChunkPos(0, 0);
+    assert!(!grid.get_chunk(chunk_pos).unwrap().revealed_any());
+    assert!(!grid.any_revealed());
+
+    let revealed = grid
+        .reveal(TilePos(2, 2), RevealOptions::default())
+        .revealed;
+    let chunk = grid.get_chunk(chunk_pos).unwrap();
+    assert!(chunk.revealed_any());
+    assert_eq!(chunk.revealed_count(), revealed.len());
+    assert!(grid.any_revealed());
+
+    // Replacing a revealed tile with another revealed tile doesn't count twice.
+    grid.set_tile(TilePos(2, 2), Tile::Mine);
+    assert_eq!(
+        grid.get_chunk(chunk_pos).unwrap().revealed_count(),
+        revealed.len()
+    );
+
+    // Parsing a chunk counts its revealed tiles.
+    let chunk = grid.get_chunk(chunk_pos).unwrap();
+    assert_eq!(chunk.to_string().parse::<Chunk>().as_ref(), Ok(chunk));
+
+    // Covering the region again resets it.
+    for pos in revealed {
+        grid.set_tile(pos, Tile::Covered(FlagState::None, HiddenState::Safe));
+    }
+    assert!(!grid.get_chunk(chunk_pos).unwrap().revealed_any());
+    assert!(!grid.any_revealed());
+}
+
+#[cfg(test)]
+#[test]
+fn test_mine_weights() {
+    let mut grid = test_grid_with_mines(&[TilePos(0, 0), TilePos(4, 0)]);
+    grid.set_mine_weight(TilePos(4, 0), 2);
+    assert_eq!(grid.mine_weight(TilePos(0, 0)), 1);
+    assert_eq!(grid.mine_weight(TilePos(4, 0)), 2);
+    grid.reveal(TilePos(1, 0), RevealOptions::default());
+    grid.reveal(TilePos(3, 0), RevealOptions::default());
+    assert_eq!(grid.get_tile(TilePos(1, 0)), Tile::Number(1));
+    assert_eq!(grid.get_tile(TilePos(3, 0)), Tile::Number(2));
+
+    // Flags count as their weight when chording.
+    let options = RevealOptions::default();
+    grid.cycle_flag(TilePos(4, 0), false);
+    assert_eq!(grid.count_assumed_mines(TilePos(3, 0), options), 2);
+    assert!(grid.can_chord(TilePos(3, 0), options));
+    assert_eq!(grid.neighbor_summary(TilePos(3, 0)).flagged, 1);
+
+    // Weights are saved.
+    let loaded = grid.to_string().parse::<Grid>().unwrap();
+    assert_eq!(loaded.mine_weight(TilePos(4, 0)), 2);
+    assert_eq!(loaded.mine_weight(TilePos(0, 0)), 1);
+    assert_eq!(loaded.content_hash(), grid.content_hash());
+    assert_eq!(
+        "weight 1,1 0\n".parse::<Grid>().unwrap_err(),
+        ParseError::InvalidGrid,
+    );
+    assert_eq!(
+        "weight 1;1 2\n".parse::<Grid>().unwrap_err(),
+        ParseError::InvalidCoordinates("1;1".to_owned()),
+    );
+
+    grid.set_mine_weight(TilePos(4, 0), 1);
+    assert_eq!(grid.mine_weight(TilePos(4, 0)), 1);
+    assert!(!grid.to_string().contains("weight"));
+
+    // The solver knows that a number's only covered neighbor is a mine if its
+    // weight makes up the number.
+    let mut grid = Grid::with_seed(0);
+    for nbr in TilePos(0, 0).neighbors() {
+        grid.set_tile(nbr, Tile::Number(0));
+    }
+    grid.set_tile(TilePos(0, 0), Tile::Number(2));
+    grid.set_tile(
+        TilePos(1, 0),
+        Tile::Covered(FlagState::None, HiddenState::Unknown),
+    );
+    assert!(super::solver::deduce(&grid, TilePos(0, 0), TilePos(0, 0)).is_empty());
+    grid.set_mine_weight(TilePos(1, 0), 2);
+    assert_eq!(
+        super::solver::deduce(&grid, TilePos(0, 0), TilePos(0, 0)).mines,
+        [TilePos(1, 0)],
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_for_each_tile_in_rect() {
+    let mut grid = test_grid_with_mines(&[]);
+    grid.set_tile(TilePos(0, 0), Tile::Number(0));
+    grid.set_track_changes(true);
+    let (min, max) = (TilePos(-2, -1), TilePos(3, 4));
+    grid.for_each_tile_in_rect(min, max, |_, tile| {
+        if let Tile::Covered(_, h) = *tile {
+            *tile = Tile::Covered(FlagState::Flag, h);
+        }
+    });
+
+    let expected: HashSet<_> = tiles_in_rect(min, max)
+        .filter(|&pos| pos != TilePos(0, 0))
+        .collect();
+    assert_eq!(grid.take_changed_tiles(), expected);
+    assert_eq!(grid.flag_count(), expected.len());
+    for pos in tiles_in_rect(TilePos(-5, -5), TilePos(5, 5)) {
+        assert_eq!(grid.get_tile(pos).is_flagged(), expected.contains(&pos));
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_arena() {
+    let mines = [TilePos(2, 2), TilePos(6, 6)];
+    let mut grid = test_grid_with_mines(&mines);
+    let (min, max) = (TilePos(0, 0), TilePos(8, 8));
+    grid.set_arena(Some((min, max)));
+    assert!(grid.in_arena(TilePos(8, 0)));
+    assert!(!grid.in_arena(TilePos(9, 0)));
+
+    // Walls can't be revealed or flagged.
+    for &wall in &[TilePos(-1, 4), TilePos(9, 9), TilePos(100, 100)] {
+        assert_eq!(
+            grid.reveal(wall, RevealOptions::default()),
+            RevealOutcome::default()
+        );
+        grid.cycle_flag(wall, false);
+        assert_eq!(grid.get_tile(wall), WALL_TILE);
+    }
+    assert_eq!(grid.flag_count(), 0);
+    assert_eq!(grid.generated_chunk_count(), 0);
+
+    // Play inside the arena is the same as without it, and openings stop at
+    // the walls.
+    let outcome = grid.reveal(TilePos(8, 0), RevealOptions::default());
+    assert!(!outcome.hit_mine);
+    assert_eq!(outcome.revealed.len(), 81 - mines.len());
+    assert!(outcome.revealed.iter().all(|&pos| grid.in_arena(pos)));
+    assert_eq!(grid.get_tile(TilePos(1, 1)), Tile::Number(1));
+    assert_eq!(grid.get_tile(TilePos(7, 7)), Tile::Number(1));
+    assert_eq!(grid.get_tile(TilePos(0, 0)), Tile::Number(0));
+    assert_eq!(grid.generated_chunk_count(), 0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_place_exact_mines() {
+    let arena = (TilePos(-3, -2), TilePos(6, 7));
+    let safe = TilePos(0, 0).neighbors().collect_vec();
+    let mine_positions = |grid: &Grid| {
+        tiles_in_rect(arena.0, arena.1)
+            .filter(|&pos| grid.get_tile(pos).is_mine())
+            .collect_vec()
+    };
+    for &count in &[0, 1, 20, 91] {
+        let mut grid = Grid::with_seed(7);
+        grid.set_arena(Some(arena));
+        grid.place_exact_mines(arena, count, 7, &safe).unwrap();
+        let mines = mine_positions(&grid);
+        assert_eq!(mines.len(), count);
+        assert!(safe.iter().all(|pos| !mines.contains(pos)));
+
+        // The same seed always gives the same mines.
+        let mut other = Grid::with_seed(7);
+        other.set_arena(Some(arena));
+        other.place_exact_mines(arena, count, 7, &safe).unwrap();
+        assert_eq!(mine_positions(&other), mines);
+    }
+
+    // Too many mines to fit leaves the grid unchanged.
+    let mut grid = Grid::with_seed(7);
+    grid.set_arena(Some(arena));
+    assert_eq!(
+        grid.place_exact_mines(arena, 92, 7, &safe),
+        Err(TooManyMines(91)),
+    );
+    assert_eq!(grid.chunk_count(), 0);
+    assert_eq!(grid.arena_mine_count(), None);
+    assert_eq!(
+        grid.place_exact_mines(arena, 101, 7, &[]),
+        Err(TooManyMines(100)),
+    );
+    grid.place_exact_mines(arena, 100, 7, &[]).unwrap();
+    assert_eq!(mine_positions(&grid).len(), 100);
+}
+
+#[cfg(test)]
+#[test]
+fn test_mines_remaining() {
+    let arena = (TilePos(0, 0), TilePos(3, 3));
+    let mut grid = Grid::with_seed(1);
+    grid.set_arena(Some(arena));
+    assert_eq!(grid.arena_mine_count(), None);
+    grid.place_exact_mines(arena, 2, 1, &[]).unwrap();
+    assert_eq!(grid.arena_mine_count(), Some(2));
+    assert_eq!(grid.mines_remaining(), 2);
+
+    grid.cycle_flag(TilePos(0, 0), false);
+    assert_eq!(grid.mines_remaining(), 1);
+    grid.cycle_flag(TilePos(1, 0), false);
+    grid.cycle_flag(TilePos(2, 0), false);
+    assert_eq!(grid.mines_remaining(), -1);
+    grid.cycle_flag(TilePos(1, 0), false);
+    assert_eq!(grid.mines_remaining(), 0);
+
+    grid.set_arena(None);
+    assert_eq!(grid.arena_mine_count(), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_flag_all_remaining_in_arena() {
+    let mut grid = Grid::with_seed(1);
+    grid.cycle_flag(TilePos(0, 0), false);
+    assert_eq!(grid.flag_all_remaining_in_arena(), 0);
+    assert_eq!(grid.flag_count(), 1);
+
+    let arena = (TilePos(0, 0), TilePos(3, 3));
+    grid.set_arena(Some(arena));
+    grid.place_exact_mines(arena, 2, 1, &[]).unwrap();
+    grid.for_each_tile_in_rect(TilePos(0, 1), TilePos(3, 1), |_, tile| {
+        *tile = Tile::Number(0)
+    });
+    grid.cycle_flag(TilePos(1, 0), true);
+    grid.cycle_flag(TilePos(1, 0), true);
+    assert!(matches!(
+        grid.get_tile(TilePos(1, 0)),
+        Tile::Covered(FlagState::Question, _)
+    ));
+
+    // Every covered tile is flagged, but revealed tiles are left alone.
+    assert_eq!(grid.flag_all_remaining_in_arena(), 11);
+    assert_eq!(grid.flag_count(), 12);
+    assert_eq!(grid.mines_remaining(), -10);
+    for pos in tiles_in_rect(arena.0, arena.1) {
+        assert_eq!(grid.get_tile(pos).is_flagged(), pos.1 != 1);
+    }
+
+    // Nothing is left to flag.
+    let before = grid.clone();
+    assert_eq!(grid.flag_all_remaining_in_arena(), 0);
+    assert_eq!(grid, before);
+}
+
+#[cfg(test)]
+#[test]
+fn test_explored_chunk_bounds() {
+    let mut grid = Grid::new();
+    assert_eq!(grid.explored_chunk_bounds(), None);
+    grid.get_chunk_mut(ChunkPos(2, -3));
+    assert_eq!(
+        grid.explored_chunk_bounds(),
+        Some((ChunkPos(2, -3), ChunkPos(2, -3))),
+    );
+    grid.get_chunk_mut(ChunkPos(-5, 1));
+    grid.get_chunk_mut(ChunkPos(0, 4));
+    assert_eq!(
+        grid.explored_chunk_bounds(),
+        Some((ChunkPos(-5, -3), ChunkPos(2, 4))),
+    );
+}
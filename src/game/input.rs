@@ -3,6 +3,8 @@ use glium::glutin::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyC
 use std::collections::HashSet;
 use std::ops::Index;
 
+use super::TilePos;
+
 const DRAG_THRESHOLD: u32 = 3;
 
 pub const KEYBD_MOVE_SPEED: f64 = 1000.0;
@@ -31,7 +33,7 @@ pub mod sc {
     pub const Z: u32 = 44;
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct Drag {
     pub button: MouseButton,
     pub tile_coords: Point2<f64>,
@@ -42,6 +44,8 @@ pub struct Drag {
     pub past_threshold: bool,
 
     pub kind: DragKind,
+    /// Set of tiles that have already been flagged during this drag.
+    pub flagged_tiles: HashSet<TilePos>,
 }
 impl Drag {
     pub fn update_cursor_end(&mut self, (x, y): (u32, u32)) {
@@ -58,6 +62,25 @@ impl Drag {
 pub enum DragKind {
     Pan,
     Scale,
+    Flag,
+}
+
+/// Returns the tiles along a line from `start` to `end`, inclusive.
+pub fn tiles_along_line(start: TilePos, end: TilePos) -> impl Iterator<Item = TilePos> {
+    let TilePos(x1, y1) = start;
+    let TilePos(x2, y2) = end;
+    let steps = start.chebyshev_distance(end);
+    (0..=steps).map(move |i| {
+        let t = if steps == 0 {
+            0.0
+        } else {
+            i as f64 / steps as f64
+        };
+        TilePos(
+            x1 + ((x2 - x1) as f64 * t).round() as i32,
+            y1 + ((y2 - y1) as f64 * t).round() as i32,
+        )
+    })
 }
 
 #[derive(Debug, Default, Clone)]
@@ -68,6 +91,14 @@ pub struct KeysPressed {
     virtual_keycodes: HashSet<VirtualKeyCode>,
 }
 impl KeysPressed {
+    /// Returns `true` if any key that pans or zooms the camera is held.
+    /// Modifiers and other keys only matter when combined with some other
+    /// input.
+    pub fn moves_camera(&self) -> bool {
+        [sc::W, sc::A, sc::S, sc::D, sc::Q, sc::E, sc::Z]
+            .iter()
+            .any(|&scancode| self[scancode])
+    }
     /// Updates internal key state based on a KeyboardInput event.
     pub fn update(&mut self, input: &KeyboardInput) {
         match input.state {
@@ -0,0 +1,259 @@
+use glium::glutin::event::{ModifiersState, VirtualKeyCode};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Action that can be triggered by a key press.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    /// Reveals the tile under the cursor.
+    Reveal,
+    /// Reveals every visible tile that is known to be safe.
+    SweepVisible,
+    /// Undoes the last move.
+    Undo,
+    /// Removes every flag and question mark.
+    ClearFlags,
+    /// Starts the same board over.
+    Retry,
+    /// Starts a new board with a random seed.
+    NewGame,
+    /// Zooms the camera to show the whole board.
+    ZoomToFit,
+    /// Shows the list of keybindings.
+    ShowHelp,
+    /// Starts or stops solving the visible part of the grid automatically.
+    ToggleAutoplay,
+    /// Switches to the next color palette.
+    CyclePalette,
+    /// Makes tiles brighter.
+    Brighten,
+    /// Makes tiles darker.
+    Darken,
+    /// Saves the game.
+    Save,
+    /// Starts or stops recording a replay.
+    ToggleRecording,
+    /// Prints the visible part of the grid as text.
+    DumpVisible,
+    /// Checks that saving and loading the game gives back the same game.
+    VerifySave,
+    /// Prints what the player can see about the tile under the cursor.
+    DescribeTile,
+    /// Makes the visible tiles the objective zone, or removes it.
+    ToggleObjectiveZone,
+}
+impl KeyAction {
+    /// List of all actions, in the order they are listed in the help.
+    pub const ALL: &'static [Self] = &[
+        KeyAction::Reveal,
+        KeyAction::SweepVisible,
+        KeyAction::Undo,
+        KeyAction::ClearFlags,
+        KeyAction::Retry,
+        KeyAction::NewGame,
+        KeyAction::ZoomToFit,
+        KeyAction::ShowHelp,
+        KeyAction::ToggleAutoplay,
+        KeyAction::CyclePalette,
+        KeyAction::Brighten,
+        KeyAction::Darken,
+        KeyAction::Save,
+        KeyAction::ToggleRecording,
+        KeyAction::DumpVisible,
+        KeyAction::VerifySave,
+        KeyAction::DescribeTile,
+        KeyAction::ToggleObjectiveZone,
+    ];
+
+    /// Returns `true` if the action switches something on or off or between
+    /// options, so it should only be triggered once each time its key is
+    /// pressed, even if the key is held long enough to repeat.
+    pub fn is_toggle(self) -> bool {
+        matches!(
+            self,
+            KeyAction::ToggleAutoplay
+                | KeyAction::CyclePalette
+                | KeyAction::ToggleRecording
+                | KeyAction::ToggleObjectiveZone
+        )
+    }
+
+    /// Returns a human-friendly description of the action.
+    pub fn description(self) -> &'static str {
+        match self {
+            KeyAction::Reveal => "Uncover the square under the cursor",
+            KeyAction::SweepVisible => "Uncover every visible square known to be safe",
+            KeyAction::Undo => "Undo the last move",
+            KeyAction::ClearFlags => "Remove every flag and question mark",
+            KeyAction::Retry => "Start this board over",
+            KeyAction::NewGame => "Start a new board",
+            KeyAction::ZoomToFit => "Zoom to show the whole board",
+            KeyAction::ShowHelp => "Show controls",
+            KeyAction::ToggleAutoplay => "Start or stop solving automatically",
+            KeyAction::CyclePalette => "Switch to the next color palette",
+            KeyAction::Brighten => "Make squares brighter",
+            KeyAction::Darken => "Make squares darker",
+            KeyAction::Save => "Save game",
+            KeyAction::ToggleRecording => "Start or stop recording a replay",
+            KeyAction::DumpVisible => "Print the visible squares as text",
+            KeyAction::VerifySave => "Check that the game saves and loads correctly",
+            KeyAction::DescribeTile => "Print information about the square under the cursor",
+            KeyAction::ToggleObjectiveZone => {
+                "Make the visible squares an objective to solve, or remove it"
+            }
+        }
+    }
+}
+
+/// Key, optionally with the Ctrl modifier, that triggers an action.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    /// Key to press.
+    pub key: VirtualKeyCode,
+    /// Whether Ctrl must be held.
+    pub ctrl: bool,
+}
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        match self.key {
+            VirtualKeyCode::Slash => write!(f, "?"),
+            VirtualKeyCode::LBracket => write!(f, "["),
+            VirtualKeyCode::RBracket => write!(f, "]"),
+            key => write!(f, "{:?}", key),
+        }
+    }
+}
+impl KeyBinding {
+    /// Returns a binding for a key without any modifiers.
+    pub fn key(key: VirtualKeyCode) -> Self {
+        Self { key, ctrl: false }
+    }
+    /// Returns a binding for a key with the Ctrl modifier.
+    pub fn ctrl(key: VirtualKeyCode) -> Self {
+        Self { key, ctrl: true }
+    }
+}
+
+/// Error returned when a key could not be bound to an action because it is
+/// already bound to another action.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyConflict(pub KeyAction);
+impl fmt::Display for KeyConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Key is already used to {}",
+            self.0.description().to_lowercase()
+        )
+    }
+}
+impl std::error::Error for KeyConflict {}
+
+/// Mapping from actions to the keys that trigger them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBindings {
+    bindings: HashMap<KeyAction, KeyBinding>,
+}
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let mut ret = Self {
+            bindings: HashMap::new(),
+        };
+        ret.set(KeyAction::Reveal, KeyBinding::key(VirtualKeyCode::Space));
+        ret.set(KeyAction::SweepVisible, KeyBinding::key(VirtualKeyCode::X));
+        ret.set(KeyAction::Undo, KeyBinding::ctrl(VirtualKeyCode::Z));
+        ret.set(
+            KeyAction::ClearFlags,
+            KeyBinding::key(VirtualKeyCode::Delete),
+        );
+        ret.set(KeyAction::Retry, KeyBinding::key(VirtualKeyCode::F5));
+        ret.set(KeyAction::NewGame, KeyBinding::key(VirtualKeyCode::F2));
+        ret.set(KeyAction::ZoomToFit, KeyBinding::key(VirtualKeyCode::Home));
+        ret.set(KeyAction::ShowHelp, KeyBinding::key(VirtualKeyCode::Slash));
+        ret.set(
+            KeyAction::ToggleAutoplay,
+            KeyBinding::key(VirtualKeyCode::P),
+        );
+        ret.set(KeyAction::CyclePalette, KeyBinding::key(VirtualKeyCode::C));
+        ret.set(
+            KeyAction::Brighten,
+            KeyBinding::key(VirtualKeyCode::RBracket),
+        );
+        ret.set(KeyAction::Darken, KeyBinding::key(VirtualKeyCode::LBracket));
+        ret.set(KeyAction::Save, KeyBinding::ctrl(VirtualKeyCode::S));
+        ret.set(
+            KeyAction::ToggleRecording,
+            KeyBinding::ctrl(VirtualKeyCode::R),
+        );
+        ret.set(KeyAction::DumpVisible, KeyBinding::key(VirtualKeyCode::F3));
+        ret.set(KeyAction::VerifySave, KeyBinding::key(VirtualKeyCode::F4));
+        ret.set(KeyAction::DescribeTile, KeyBinding::key(VirtualKeyCode::F6));
+        ret.set(
+            KeyAction::ToggleObjectiveZone,
+            KeyBinding::key(VirtualKeyCode::F7),
+        );
+        ret
+    }
+}
+impl KeyBindings {
+    /// Returns the key bound to an action, or `None` if it is unbound.
+    pub fn get(&self, action: KeyAction) -> Option<KeyBinding> {
+        self.bindings.get(&action).copied()
+    }
+    /// Binds a key to an action, replacing its previous binding.
+    ///
+    /// If another action is bound to the same key, then whichever comes first
+    /// in `KeyAction::ALL` is triggered by it. Use `try_set()` to prevent this.
+    pub fn set(&mut self, action: KeyAction, binding: KeyBinding) {
+        self.bindings.insert(action, binding);
+    }
+    /// Binds a key to an action like `set()`, unless another action is already
+    /// bound to the same key.
+    pub fn try_set(&mut self, action: KeyAction, binding: KeyBinding) -> Result<(), KeyConflict> {
+        match self.action_for_binding(binding) {
+            Some(other) if other != action => Err(KeyConflict(other)),
+            _ => {
+                self.set(action, binding);
+                Ok(())
+            }
+        }
+    }
+    /// Returns the action bound to a key, if any.
+    pub fn action_for_binding(&self, binding: KeyBinding) -> Option<KeyAction> {
+        KeyAction::ALL
+            .iter()
+            .copied()
+            .find(|&action| self.get(action) == Some(binding))
+    }
+
+    /// Returns the action triggered by a key press, if any.
+    pub fn action_for_key(
+        &self,
+        key: VirtualKeyCode,
+        modifiers: ModifiersState,
+    ) -> Option<KeyAction> {
+        let pressed = if modifiers == ModifiersState::CTRL {
+            KeyBinding::ctrl(key)
+        } else if modifiers.ctrl() || modifiers.alt() || modifiers.logo() {
+            return None;
+        } else {
+            KeyBinding::key(key)
+        };
+        self.action_for_binding(pressed)
+    }
+
+    /// Returns one line for each bound action, describing the key and what
+    /// it does.
+    pub fn help_lines(&self) -> Vec<String> {
+        KeyAction::ALL
+            .iter()
+            .filter_map(|&action| {
+                let binding = self.get(action)?;
+                Some(format!("{}: {}", binding, action.description()))
+            })
+            .collect()
+    }
+}
@@ -3,6 +3,7 @@ use glium::glutin::event::{
     ElementState, ModifiersState, MouseButton, MouseScrollDelta, ScanCode, VirtualKeyCode,
     WindowEvent,
 };
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::str::FromStr;
 use std::time::Duration;
@@ -10,56 +11,234 @@ use std::time::Duration;
 mod camera;
 mod grid;
 mod input;
+mod keybindings;
+mod parse;
+mod replay;
 mod scale;
+mod settings;
+mod solver;
+mod stats;
 mod tile;
 
 pub use camera::Camera;
-pub use grid::{Chunk, ChunkPos, Grid, TilePos, CHUNK_SIZE};
+pub use grid::{
+    chunks_in_rect, tiles_in_rect, Chunk, Grid, RevealOptions, RevealOutcome, TilePos,
+    TooManyMines, WouldHitMine, CHUNK_SIZE,
+};
+pub use keybindings::{KeyAction, KeyBinding, KeyBindings, KeyConflict};
+pub use parse::ParseError;
+pub use replay::{Action, Replay};
 pub use scale::Scale;
+pub use settings::{FirstReveal, Palette, Settings};
+pub use stats::{BestTimes, GameStats};
 pub use tile::{FlagState, HiddenState, Tile};
 
 pub const MINE_DENSITY: f64 = 0.2;
+/// Version of the save file format written by `Game`.
+///
+/// Saves from before versions were added are version 1, which has no version
+/// line or seed. Version 3 adds the camera scale after the camera position, so
+/// that older versions of the game don't misread it. Version 4 adds an
+/// optional line after the seed, `zone x,y x,y`, giving the corners of the
+/// objective zone, and lines `weight x,y n` before the first chunk for mines
+/// that count as something other than one.
+pub const SAVE_FORMAT_VERSION: &str = "v4";
+/// Maximum number of moves that can be undone.
+const UNDO_HISTORY_LIMIT: usize = 100;
+/// Maximum number of times to place the mines in a finite game when looking
+/// for a board that can be solved without guessing.
+pub const MAX_NO_GUESS_ATTEMPTS: u64 = 100;
+/// How long a flag takes to be planted after it is placed.
+pub const FLAG_PLANT_DURATION: Duration = Duration::from_millis(120);
+/// Space in pixels to leave around the board when zooming to show all of it.
+pub const ZOOM_TO_FIT_MARGIN: u32 = 32;
 pub const SAVE_FILE_NAME: &str = "infinite_minesweeper_data.txt";
+pub const REPLAY_FILE_NAME: &str = "infinite_minesweeper_replay.txt";
+pub const STATS_FILE_NAME: &str = "infinite_minesweeper_stats.jsonl";
+pub const BEST_TIMES_FILE_NAME: &str = "infinite_minesweeper_best_times.json";
+
+/// Whether the game is still in progress.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum GameState {
+    /// The game is in progress.
+    #[default]
+    Playing,
+    /// The player has revealed a mine.
+    Lost,
+    /// The player has completed the objective.
+    Won,
+}
+
+/// Classic finite game size.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Preset {
+    /// 10 mines in a 9x9 arena.
+    Beginner,
+    /// 40 mines in a 16x16 arena.
+    Intermediate,
+    /// 99 mines in a 30x16 arena.
+    Expert,
+}
+impl Preset {
+    /// List of all presets, from easiest to hardest.
+    pub const ALL: &'static [Self] = &[Preset::Beginner, Preset::Intermediate, Preset::Expert];
+
+    /// Returns the width and height of the arena and the number of mines in
+    /// it.
+    pub fn size(self) -> (i32, i32, usize) {
+        match self {
+            Preset::Beginner => (9, 9, 10),
+            Preset::Intermediate => (16, 16, 40),
+            Preset::Expert => (30, 16, 99),
+        }
+    }
+    /// Returns the name of the preset in lowercase, as used in files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Beginner => "beginner",
+            Preset::Intermediate => "intermediate",
+            Preset::Expert => "expert",
+        }
+    }
+}
+
+/// Information about a tile that the player can see, such as for a tooltip.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TileInfo {
+    /// Tile as the player sees it. Covered tiles have an unknown hidden state.
+    pub tile: Tile,
+    /// Number of neighboring tiles that are flagged.
+    pub flagged_neighbors: u8,
+    /// Number of neighboring tiles that are covered and not flagged.
+    pub covered_neighbors: u8,
+    /// Whether revealing the tile would reveal its neighbors, because it is a
+    /// number with the correct number of flags around it.
+    pub chord_available: bool,
+    /// ID of the reveal that opened the tile, which is the same for every tile
+    /// opened by one click, or `None` if reveals are not being tracked. They
+    /// are only tracked in practice games.
+    pub opened_by: Option<u32>,
+}
+
+/// Changes made by one move, which can be undone.
+#[derive(Debug, Clone)]
+struct UndoEntry {
+    /// Value of each changed tile before the move.
+    previous_tiles: HashMap<TilePos, Tile>,
+    /// Whether the game was still in progress before the move.
+    state: GameState,
+    /// Whether any tile had been revealed before the move.
+    has_revealed: bool,
+}
 
 #[derive(Debug, Default, Clone)]
 pub struct Game {
     /// Tile grid.
     pub grid: Grid,
+    /// User-configurable settings.
+    pub settings: Settings,
+    /// Whether the game is still in progress.
+    state: GameState,
+    /// Whether any tile has been revealed yet.
+    has_revealed: bool,
+    /// Classic game size being played, if any.
+    preset: Option<Preset>,
+    /// Fastest wins for each preset.
+    best_times: BestTimes,
+    /// Rectangle of tiles that must be solved to win, if any.
+    objective_zone: Option<(TilePos, TilePos)>,
+    /// Total time spent playing.
+    elapsed: Duration,
+    /// Recording in progress.
+    recorder: Option<replay::Recorder>,
+    /// Replay in progress.
+    playback: Option<replay::Playback>,
+    /// Whether to solve the visible part of the grid automatically.
+    autoplay: bool,
+    /// Number of chunks generated by automatic solving during this frame.
+    chunks_generated_this_frame: usize,
+    /// Moves that can be undone, from oldest to newest.
+    undo_history: VecDeque<UndoEntry>,
+
     /// Camera.
     pub camera: Camera,
     /// Interpolation target camera.
-    pub camera_target: Camera,
+    camera_target: Camera,
+    /// Time since the camera started shaking, if it is shaking.
+    shake_time: Option<Duration>,
+    /// Total time that frames have been drawn for, used to time animations.
+    animation_time: Duration,
+    /// Value of `animation_time` when each flag that is still being planted
+    /// was placed.
+    flags_planted_at: HashMap<TilePos, Duration>,
+
+    /// Risky tile that will be revealed if the player tries to reveal it
+    /// again.
+    pending_reveal: Option<TilePos>,
 
     /// Position of the mouse cursor.
     cursor_pos: Option<(u32, u32)>,
     /// Mouse drag in progress.
     drag: Option<input::Drag>,
 
+    /// Keys that trigger actions.
+    keybindings: KeyBindings,
     /// Set of pressed keys.
     keys: input::KeysPressed,
+    /// Keys that have triggered a toggle action since they were last
+    /// released.
+    held_toggle_keys: HashSet<VirtualKeyCode>,
     /// Set of pressed modifiers.
     modifiers: ModifiersState,
 }
 impl fmt::Display for Game {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let cam_pos = self.camera_target.center();
-        write!(f, "{},{}*\n\n{}", cam_pos.x, cam_pos.y, self.grid)
+        writeln!(f, "{}", SAVE_FORMAT_VERSION)?;
+        writeln!(f, "{}", self.grid.seed())?;
+        if let Some((min, max)) = self.objective_zone {
+            writeln!(f, "zone {} {}", min, max)?;
+        }
+        write!(f, "{}*\n\n{}", self.camera_target, self.grid)
     }
 }
 impl FromStr for Game {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut ret = Self::new();
 
-        let (cam_pos, grid) = s.split_once('*').ok_or(())?;
-        let (cam_x, cam_y) = cam_pos.split_once(',').ok_or(())?;
+        let mut seed = None;
+        let mut s = s;
+        if s.starts_with('v') {
+            let (version, rest) = s.split_once('\n').unwrap_or((s, ""));
+            match version.trim() {
+                "v2" | "v3" | "v4" => {
+                    let (seed_str, rest) = rest.split_once('\n').unwrap_or((rest, ""));
+                    let invalid_seed = || ParseError::InvalidSeed(seed_str.trim().to_owned());
+                    seed = Some(seed_str.trim().parse().map_err(|_| invalid_seed())?);
+                    s = rest;
+                }
+                other => return Err(ParseError::UnknownVersion(other.to_owned())),
+            }
+        }
+        if let Some(rest) = s.strip_prefix("zone ") {
+            let (zone, rest) = rest.split_once('\n').unwrap_or((rest, ""));
+            let (min, max) = zone
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| ParseError::InvalidCoordinates(zone.trim().to_owned()))?;
+            ret.objective_zone = Some((min.parse()?, max.parse()?));
+            s = rest;
+        }
 
-        ret.camera_target.set_center(Point2::new(
-            cam_x.trim().parse().map_err(|_| ())?,
-            cam_y.trim().parse().map_err(|_| ())?,
-        ));
+        let (camera, grid) = s.split_once('*').ok_or(ParseError::MissingCameraPosition)?;
+        ret.camera_target = camera.parse()?;
         ret.grid = grid.parse()?;
+        // Version 1 saves don't have a seed, so keep the random one.
+        if let Some(seed) = seed {
+            ret.grid.set_seed(seed);
+        }
+        ret.has_revealed = ret.grid.any_revealed();
 
         Ok(ret)
     }
@@ -69,9 +248,761 @@ impl Game {
     pub fn new() -> Self {
         Game::default()
     }
+    /// Constructs a new practice game, which is never saved so that it does
+    /// not overwrite the main game. Practice games track which click opened
+    /// each tile, so that the player can see what each click did.
+    pub fn practice() -> Self {
+        let mut ret = Game::new();
+        ret.settings.practice_mode = true;
+        ret.settings.save_file = None;
+        ret.grid.set_track_opened_by(true);
+        ret
+    }
+
+    /// Constructs a classic beginner game, with 10 mines in a 9x9 arena.
+    pub fn beginner() -> Self {
+        Self::with_preset(Preset::Beginner)
+    }
+    /// Constructs a classic intermediate game, with 40 mines in a 16x16
+    /// arena.
+    pub fn intermediate() -> Self {
+        Self::with_preset(Preset::Intermediate)
+    }
+    /// Constructs a classic expert game, with 99 mines in a 30x16 arena.
+    pub fn expert() -> Self {
+        Self::with_preset(Preset::Expert)
+    }
+    /// Constructs a classic game of a preset size. Wins are timed against the
+    /// best time for the preset.
+    pub fn with_preset(preset: Preset) -> Self {
+        let (width, height, mines) = preset.size();
+        let mut ret =
+            Self::with_arena(width, height, mines).expect("preset has too many mines to fit");
+        ret.preset = Some(preset);
+        // Like classic Minesweeper, start every preset game with an opening.
+        ret.settings.first_reveal = FirstReveal::Opening;
+        ret.settings.avoid_guessing = true;
+        ret
+    }
+    /// Constructs a finite game, with exactly `mines` mines in an arena
+    /// `width` tiles wide and `height` tiles tall, or returns an error if there
+    /// are more mines than tiles. Arena games are not saved.
+    pub fn with_arena(width: i32, height: i32, mines: usize) -> Result<Self, TooManyMines> {
+        let mut ret = Game::new();
+        let arena = (TilePos(0, 0), TilePos(width - 1, height - 1));
+        ret.grid.set_arena(Some(arena));
+        let seed = ret.grid.seed();
+        ret.grid.place_exact_mines(arena, mines, seed, &[])?;
+        ret.camera_target
+            .set_center(Point2::new(width as f64 / 2.0, height as f64 / 2.0));
+        ret.camera = ret.camera_target;
+        Ok(ret)
+    }
+
+    /// Returns the seed used to place mines.
+    pub fn seed(&self) -> u64 {
+        self.grid.seed()
+    }
+    /// Sets the seed used to place mines.
+    ///
+    /// Mines that have already been placed would not match the new seed, so if
+    /// any chunks exist then the grid is cleared and the game starts over.
+    pub fn set_seed(&mut self, seed: u64) {
+        if self.grid.chunk_count() == 0 {
+            self.grid.set_seed(seed);
+            return;
+        }
+        self.restart(seed);
+    }
+    /// Starts the same board over, with the same seed and arena, so that the
+    /// same moves give the same result.
+    pub fn retry(&mut self) {
+        self.restart(self.grid.seed());
+    }
+    /// Starts a new board with a random seed, keeping the arena and its number
+    /// of mines if there is one.
+    pub fn new_random(&mut self) {
+        self.restart(rand::random());
+    }
+    /// Clears the grid and starts the game over with a seed, keeping the arena
+    /// and its number of mines if there is one.
+    fn restart(&mut self, seed: u64) {
+        let arena_mines = self.grid.arena().zip(self.grid.arena_mine_count());
+        self.grid.clear_with_seed(seed);
+        if let Some((arena, count)) = arena_mines {
+            self.grid
+                .place_exact_mines(arena, count, seed, &[])
+                .expect("arena already held this many mines");
+        }
+        self.undo_history.clear();
+        self.state = GameState::Playing;
+        self.has_revealed = false;
+        self.elapsed = Duration::default();
+        self.pending_reveal = None;
+        self.shake_time = None;
+        self.flags_planted_at.clear();
+        self.autoplay = false;
+        self.playback = None;
+        // Moves from before the restart can't be played back on the new board.
+        if self.recorder.is_some() {
+            self.start_recording();
+        }
+    }
+
+    /// Returns the title of the window, which reflects the state of the game.
+    /// After winning a classic game, it also shows the best time for its size.
+    pub fn window_title(&self) -> String {
+        match self.state {
+            GameState::Playing if self.grid.arena_mine_count().is_some() => {
+                format!(
+                    "{} — {} mines left",
+                    crate::TITLE,
+                    self.grid.mines_remaining()
+                )
+            }
+            GameState::Playing => crate::TITLE.to_owned(),
+            GameState::Lost => format!("{} — Game Over", crate::TITLE),
+            GameState::Won => match self.preset().and_then(|p| Some((p, self.best_time(p)?))) {
+                Some((preset, best)) => format!(
+                    "{} — You Win! Best {} time: {:.3}s",
+                    crate::TITLE,
+                    preset.name(),
+                    best.as_secs_f64(),
+                ),
+                None => format!("{} — You Win!", crate::TITLE),
+            },
+        }
+    }
+
+    /// Returns the camera that `camera` is interpolating toward.
+    ///
+    /// Only tests use this so far; it is for anything outside the game, such
+    /// as a minimap, that needs to know where the view is headed without being
+    /// able to move it.
+    #[allow(dead_code)]
+    pub fn camera_target(&self) -> Camera {
+        self.camera_target
+    }
+    /// Returns `true` if the camera has reached its interpolation target.
+    pub fn camera_settled(&self) -> bool {
+        self.camera
+            .approx_eq(self.camera_target, camera::CAMERA_EPSILON)
+    }
+    /// Returns `true` if nothing will change on screen until the next input,
+    /// so that frames can stop until then. `had_input` is whether any input
+    /// was handled since the last frame.
+    pub fn is_idle(&self, had_input: bool) -> bool {
+        !had_input && !self.has_continuous_updates() && self.camera_settled()
+    }
+    /// Returns `true` if the game changes every frame even without new input,
+    /// such as while solving automatically, playing back a replay, dragging,
+    /// holding movement keys, or panning at the edge of the window.
+    fn has_continuous_updates(&self) -> bool {
+        let edge_panning = match self.cursor_pos {
+            Some(cursor) if self.settings.edge_pan => {
+                let target_dimensions = self.camera.target_dimensions();
+                Self::edge_pan_direction(cursor, target_dimensions, &self.settings)
+                    != Vector2::new(0.0, 0.0)
+            }
+            _ => false,
+        };
+        self.autoplay
+            || self.shake_time.is_some()
+            || !self.flags_planted_at.is_empty()
+            || self.playback.is_some()
+            || self.drag.is_some()
+            || self.keys.moves_camera()
+            || edge_panning
+    }
+    /// Moves the camera to show the whole arena, or every chunk explored so far
+    /// in an infinite game.
+    pub fn zoom_to_fit(&mut self) {
+        let (min, max) = match (self.grid.arena(), self.grid.explored_chunk_bounds()) {
+            (Some(arena), _) => arena,
+            (None, Some((min, max))) => (min.min_tile(), max.max_tile()),
+            (None, None) => return,
+        };
+        self.camera_target.fit(min, max, ZOOM_TO_FIT_MARGIN);
+    }
+    /// Stops any camera motion in progress, leaving the camera where it is.
+    pub fn stop_camera(&mut self) {
+        self.camera_target = self.camera;
+    }
+
+    /// Returns information about a tile that the player can see, without
+    /// placing mines or creating chunks.
+    pub fn tile_info(&self, pos: TilePos) -> TileInfo {
+        let tile = match self.grid.get_tile(pos) {
+            Tile::Covered(f, _) => Tile::Covered(f, HiddenState::Unknown),
+            other => other,
+        };
+        let summary = self.grid.neighbor_summary(pos);
+        TileInfo {
+            tile,
+            flagged_neighbors: summary.flagged,
+            covered_neighbors: summary.covered,
+            chord_available: self.grid.can_chord(pos, self.reveal_options()),
+            // Undoing a reveal covers the tile without forgetting what opened it.
+            opened_by: match tile {
+                Tile::Covered(_, _) => None,
+                _ => self.grid.opened_by(pos),
+            },
+        }
+    }
+
+    /// Returns the rectangle of tiles that must be solved to win, if any.
+    pub fn objective_zone(&self) -> Option<(TilePos, TilePos)> {
+        self.objective_zone
+    }
+    /// Sets a rectangle of tiles, including `min` and `max`, that must be
+    /// solved to win. The game is won once every mine in the rectangle is
+    /// flagged and every other tile in the rectangle is revealed.
+    pub fn set_objective_zone(&mut self, min: TilePos, max: TilePos) {
+        self.objective_zone = Some((min, max));
+        self.update_state();
+    }
+    /// Removes the objective zone, so that the game cannot be won.
+    pub fn clear_objective_zone(&mut self) {
+        self.objective_zone = None;
+    }
+
+    /// Returns the rules for revealing tiles from the settings.
+    fn reveal_options(&self) -> RevealOptions {
+        RevealOptions {
+            question_marks_as_flags: self.settings.question_marks_as_flags,
+            reveal_question_marked: self.settings.reveal_question_marked,
+        }
+    }
+    /// Reveals a square and updates the game state accordingly. Does nothing
+    /// once the game is over.
+    pub fn reveal(&mut self, pos: TilePos) -> RevealOutcome {
+        let options = self.reveal_options();
+        self.reveal_with(pos, |grid, pos| Ok(grid.reveal(pos, options)))
+            .unwrap_or_default()
+    }
+    /// Reveals a square like `reveal()`, but refuses to reveal any mine. See
+    /// `Grid::reveal_safe()`.
+    pub fn reveal_safe(&mut self, pos: TilePos) -> Result<RevealOutcome, WouldHitMine> {
+        let options = self.reveal_options();
+        self.reveal_with(pos, |grid, pos| grid.reveal_safe(pos, options))
+    }
+    fn reveal_with(
+        &mut self,
+        pos: TilePos,
+        reveal: impl FnOnce(&mut Grid, TilePos) -> Result<RevealOutcome, WouldHitMine>,
+    ) -> Result<RevealOutcome, WouldHitMine> {
+        if self.state != GameState::Playing {
+            return Ok(RevealOutcome::default());
+        }
+        let undo_entry = self.begin_undo_entry();
+        self.pending_reveal = None;
+        if !self.has_revealed {
+            self.prepare_first_reveal(pos);
+        }
+        let result = reveal(&mut self.grid, pos);
+        if let Ok(outcome) = &result {
+            self.record(Action::Reveal(pos));
+            if outcome.hit_mine {
+                self.end_game(GameState::Lost);
+            }
+        }
+        self.update_state();
+        self.end_undo_entry(undo_entry);
+        result
+    }
+    /// Moves mines away from the first tile revealed, according to the
+    /// first-reveal setting.
+    fn prepare_first_reveal(&mut self, pos: TilePos) {
+        match self.grid.get_tile(pos) {
+            Tile::Covered(FlagState::Flag, _) => return,
+            Tile::Covered(FlagState::Question, _) if !self.settings.reveal_question_marked => {
+                return
+            }
+            Tile::Covered(_, _) => (),
+            _ => return,
+        }
+        self.has_revealed = true;
+        let safe_tiles = match self.settings.first_reveal {
+            FirstReveal::SafeTile => vec![pos],
+            FirstReveal::Opening => std::iter::once(pos).chain(pos.neighbors()).collect(),
+        };
+        match self.grid.arena() {
+            // Keep the exact number of mines in a finite game by placing them
+            // all over again.
+            Some((min, max)) => {
+                let mine_count = grid::tiles_in_rect(min, max)
+                    .filter(|&p| self.grid.get_tile(p).is_mine())
+                    .count();
+                // Each attempt uses a different seed derived from the grid's,
+                // so that the same board comes back when the game is retried.
+                let seed = self.grid.seed();
+                for attempt in 0..MAX_NO_GUESS_ATTEMPTS {
+                    let seed = seed.wrapping_add(attempt);
+                    let placed = self
+                        .grid
+                        .place_exact_mines((min, max), mine_count, seed, &safe_tiles)
+                        // If the arena is too crowded for an opening, only keep
+                        // the revealed tile safe.
+                        .or_else(|_| {
+                            self.grid
+                                .place_exact_mines((min, max), mine_count, seed, &[pos])
+                        });
+                    // If even that doesn't fit, every tile is a mine.
+                    if placed.is_err() || !self.settings.avoid_guessing {
+                        break;
+                    }
+                    let mut preview = self.grid.clone();
+                    preview.reveal(pos, self.reveal_options());
+                    if solver::rate_difficulty(&preview, (min, max))
+                        != solver::Difficulty::RequiresGuess
+                    {
+                        break;
+                    }
+                }
+            }
+            None => self.grid.relocate_mines(&safe_tiles),
+        }
+    }
+    /// Toggles the flag on a square, or turns it into a question mark if
+    /// question marks are enabled. Does nothing once the game is over.
+    pub fn toggle_flag(&mut self, pos: TilePos) {
+        if self.state != GameState::Playing {
+            return;
+        }
+        let undo_entry = self.begin_undo_entry();
+        self.pending_reveal = None;
+        self.record(Action::ToggleFlag(pos));
+        self.grid
+            .cycle_flag(pos, self.settings.question_marks_enabled);
+        if self.grid.get_tile(pos).is_flagged() {
+            self.flags_planted_at.insert(pos, self.animation_time);
+        } else {
+            self.flags_planted_at.remove(&pos);
+        }
+        self.update_state();
+        self.end_undo_entry(undo_entry);
+    }
+    /// Removes every flag and question mark, without affecting revealed
+    /// tiles, so that the player can start marking mines again. This can be
+    /// undone. Does nothing once the game is over.
+    pub fn clear_flags(&mut self) {
+        if self.state != GameState::Playing {
+            return;
+        }
+        let undo_entry = self.begin_undo_entry();
+        self.pending_reveal = None;
+        self.record(Action::ClearFlags);
+        self.grid.reset_flags();
+        self.flags_planted_at.clear();
+        self.update_state();
+        self.end_undo_entry(undo_entry);
+    }
+    /// Flags a covered tile, leaving it unchanged if it is already flagged.
+    ///
+    /// This is recorded as toggling the flag once or twice, so that it can be
+    /// replayed.
+    pub fn flag(&mut self, pos: TilePos) {
+        if self.state != GameState::Playing {
+            return;
+        }
+        let undo_entry = self.begin_undo_entry();
+        if let Tile::Covered(FlagState::Question, _) = self.grid.get_tile(pos) {
+            self.toggle_flag(pos);
+        }
+        if let Tile::Covered(FlagState::None, _) = self.grid.get_tile(pos) {
+            self.toggle_flag(pos);
+        }
+        self.end_undo_entry(undo_entry);
+    }
+    /// Returns the time since each flag that is still being planted was
+    /// placed.
+    pub fn flag_plant_ages(&self) -> HashMap<TilePos, Duration> {
+        self.flags_planted_at
+            .iter()
+            .map(|(&pos, &planted_at)| (pos, self.animation_time - planted_at))
+            .collect()
+    }
+
+    /// Starts grouping changes into a single move that can be undone. Returns
+    /// `None` if a move is already in progress, in which case changes are
+    /// added to that move instead.
+    fn begin_undo_entry(&mut self) -> Option<UndoEntry> {
+        if self.grid.tracks_previous_tiles() {
+            return None;
+        }
+        self.grid.set_track_previous_tiles(true);
+        Some(UndoEntry {
+            previous_tiles: HashMap::new(),
+            state: self.state,
+            has_revealed: self.has_revealed,
+        })
+    }
+    /// Finishes a move started by `begin_undo_entry()`, adding it to the undo
+    /// history if it changed anything.
+    fn end_undo_entry(&mut self, entry: Option<UndoEntry>) {
+        if let Some(mut entry) = entry {
+            entry.previous_tiles = self.grid.take_previous_tiles();
+            self.grid.set_track_previous_tiles(false);
+            if !entry.previous_tiles.is_empty() || entry.state != self.state {
+                if self.undo_history.len() >= UNDO_HISTORY_LIMIT {
+                    self.undo_history.pop_front();
+                }
+                self.undo_history.push_back(entry);
+            }
+        }
+    }
+    /// Undoes the last move, including any change to whether the game is in
+    /// progress. Returns `false` if there is nothing to undo.
+    ///
+    /// Once the game is lost, moves can only be undone in practice mode.
+    pub fn undo(&mut self) -> bool {
+        if self.state == GameState::Lost && !self.settings.practice_mode {
+            return false;
+        }
+        let entry = match self.undo_history.pop_back() {
+            Some(entry) => entry,
+            None => return false,
+        };
+        self.pending_reveal = None;
+        self.record(Action::Undo);
+        for (pos, tile) in entry.previous_tiles {
+            self.grid.set_tile(pos, tile);
+        }
+        self.state = entry.state;
+        self.has_revealed = entry.has_revealed;
+        true
+    }
+    /// Checks whether the player has won.
+    fn update_state(&mut self) {
+        if self.state != GameState::Playing {
+            return;
+        }
+        // Like the mines in an arena, the mines in the objective zone are only
+        // placed once the first tile has been revealed, so that it is safe.
+        if let (Some((min, max)), true) = (self.objective_zone, self.has_revealed) {
+            for chunk_pos in grid::chunks_in_rect(min, max) {
+                self.grid.place_mines_in_chunk(chunk_pos);
+            }
+            if self.grid.is_solved_region(min, max) {
+                self.end_game(GameState::Won);
+            }
+        }
+        // In a finite game, the game is won once every tile in the arena that
+        // isn't a mine is revealed.
+        if let (Some((min, max)), Some(mine_count)) =
+            (self.grid.arena(), self.grid.arena_mine_count())
+        {
+            // Tiles outside the arena can't be revealed, so every revealed
+            // tile is in the arena.
+            let area = (max.0 - min.0 + 1) as usize * (max.1 - min.1 + 1) as usize;
+            if self.grid.revealed_count() + mine_count >= area {
+                // Every tile still covered must be a mine.
+                self.grid.flag_all_remaining_in_arena();
+                self.end_game(GameState::Won);
+            }
+        }
+    }
+    /// Ends the game with a win or loss, saving statistics if enabled.
+    fn end_game(&mut self, state: GameState) {
+        let was_playing = self.state == GameState::Playing;
+        self.state = state;
+        if !was_playing {
+            return;
+        }
+        if state == GameState::Lost && self.settings.shake_intensity > 0.0 {
+            self.shake_time = Some(Duration::ZERO);
+        }
+        if let Some(path) = &self.settings.stats_file {
+            if let Err(e) = self.stats().append_to_file(path) {
+                eprintln!("Failed to save statistics to {}: {}", path.display(), e);
+            }
+        }
+        if let (GameState::Won, Some(preset)) = (state, self.preset) {
+            if self.best_times.record(preset, self.elapsed) {
+                eprintln!(
+                    "New best time for {}: {:.3}s",
+                    preset.name(),
+                    self.elapsed.as_secs_f64(),
+                );
+                self.save_best_times();
+            }
+        }
+    }
+    /// Returns the classic game size being played, if any.
+    pub fn preset(&self) -> Option<Preset> {
+        self.preset
+    }
+    /// Returns the fastest win for a preset, or `None` if it has never been
+    /// won.
+    pub fn best_time(&self, preset: Preset) -> Option<Duration> {
+        self.best_times.get(preset)
+    }
+    /// Loads best times from the file in settings, if there is one. A missing
+    /// file means there are no best times yet.
+    pub fn load_best_times(&mut self) {
+        if let Some(path) = &self.settings.best_times_file {
+            match BestTimes::load_from_file(path) {
+                Ok(best_times) => self.best_times = best_times,
+                Err(e) => eprintln!("Failed to load best times from {}: {}", path.display(), e),
+            }
+        }
+    }
+    fn save_best_times(&self) {
+        if let Some(path) = &self.settings.best_times_file {
+            if let Err(e) = self.best_times.save_to_file(path) {
+                eprintln!("Failed to save best times to {}: {}", path.display(), e);
+            }
+        }
+    }
+    /// Returns statistics about the game so far.
+    pub fn stats(&self) -> GameStats {
+        let mine_density = match (self.grid.arena(), self.grid.arena_mine_count()) {
+            (Some((TilePos(x1, y1), TilePos(x2, y2))), Some(count)) => {
+                let area = (x2 as f64 - x1 as f64 + 1.0) * (y2 as f64 - y1 as f64 + 1.0);
+                count as f64 / area
+            }
+            _ => MINE_DENSITY,
+        };
+        GameStats {
+            seed: self.grid.seed(),
+            mine_density,
+            elapsed: self.elapsed,
+            tiles_revealed: self.grid.revealed_count(),
+            flags_placed: self.grid.flag_count(),
+            outcome: self.state,
+        }
+    }
+    /// Reveals a square in response to player input, first asking for
+    /// confirmation if enabled and the square is not known to be safe.
+    fn reveal_with_confirmation(&mut self, pos: TilePos) {
+        if self.settings.confirm_risky_reveals
+            && self.pending_reveal != Some(pos)
+            && self.is_risky_reveal(pos)
+        {
+            self.pending_reveal = Some(pos);
+        } else {
+            self.reveal(pos);
+        }
+    }
+    /// Returns `true` if a tile is covered and adjacent to numbers, none of
+    /// which already has the correct number of flags around it.
+    ///
+    /// The first reveal and tiles away from any number are never risky, since
+    /// there is nothing to check them against.
+    fn is_risky_reveal(&self, pos: TilePos) -> bool {
+        if !self.has_revealed {
+            return false;
+        }
+        match self.grid.get_tile(pos) {
+            Tile::Covered(FlagState::Flag, _) => false,
+            Tile::Covered(_, _) => {
+                let numbers = pos
+                    .neighbors()
+                    .filter_map(|nbr| match self.grid.get_tile(nbr) {
+                        Tile::Number(n) => Some((nbr, n)),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                !numbers.is_empty()
+                    && !numbers.iter().any(|&(nbr, n)| {
+                        self.grid.count_assumed_mines(nbr, self.reveal_options()) == n
+                    })
+            }
+            _ => false,
+        }
+    }
+    /// Performs an action.
+    pub fn do_action(&mut self, action: Action) {
+        match action {
+            Action::Reveal(pos) => {
+                self.reveal(pos);
+            }
+            Action::ToggleFlag(pos) => self.toggle_flag(pos),
+            Action::Undo => {
+                self.undo();
+            }
+            Action::ClearFlags => self.clear_flags(),
+        }
+    }
+
+    /// Reveals every tile near the camera that can be deduced to be safe and
+    /// flags every tile that can be deduced to be a mine.
+    ///
+    /// Stops early once `max_chunks_generated_per_frame` chunks have been
+    /// generated this frame, leaving the rest for the next frame.
+    ///
+    /// Returns `false` if no progress could be made without guessing, or if a
+    /// deduction turned out to be wrong because the player placed a wrong flag.
+    pub fn autoplay_step(&mut self) -> bool {
+        let (TilePos(x1, y1), TilePos(x2, y2)) = self.camera.visible_tile_rect();
+        let deductions =
+            solver::deduce(&self.grid, TilePos(x1 - 1, y1 - 1), TilePos(x2 + 1, y2 + 1));
+        for &pos in &deductions.mines {
+            self.flag(pos);
+        }
+        for &pos in &deductions.safe {
+            let old_count = self.grid.generated_chunk_count();
+            if self.reveal_safe(pos).is_err() {
+                return false;
+            }
+            self.chunks_generated_this_frame += self.grid.generated_chunk_count() - old_count;
+            if self.chunks_generated_this_frame >= self.settings.max_chunks_generated_per_frame {
+                break;
+            }
+        }
+        !deductions.is_empty()
+    }
+
+    /// Reveals every covered tile in view that can be deduced to be safe from
+    /// the numbers and flags around it. No other tiles are revealed. Like
+    /// chording, this trusts the player's flags, so a wrong flag can reveal a
+    /// mine; if any number nearby has too many flags, nothing is revealed.
+    pub fn sweep_visible(&mut self) -> RevealOutcome {
+        let (min, max) = self.camera.visible_tile_rect();
+        let undo_entry = self.begin_undo_entry();
+        let outcome = self.reveal_deduced_safe_in_rect(min, max);
+        self.end_undo_entry(undo_entry);
+        outcome
+    }
+    /// Reveals every tile near `center` (within the `reveal_radius` setting)
+    /// that can be deduced to be safe. This trusts flags the same way as
+    /// `sweep_visible()`.
+    pub fn reveal_radius(&mut self, center: TilePos) -> RevealOutcome {
+        let r = self.settings.reveal_radius as i32;
+        let undo_entry = self.begin_undo_entry();
+        let outcome = self.reveal_deduced_safe_in_rect(center.offset(-r, -r), center.offset(r, r));
+        self.end_undo_entry(undo_entry);
+        outcome
+    }
+    /// Reveals every covered tile in a rectangle, including `min` and `max`,
+    /// that can be deduced to be safe, unless a number that the deductions
+    /// could depend on has more flags around it than its value.
+    fn reveal_deduced_safe_in_rect(&mut self, min: TilePos, max: TilePos) -> RevealOutcome {
+        let (TilePos(x1, y1), TilePos(x2, y2)) = (min, max);
+        let mut outcome = RevealOutcome::default();
+        // Deductions only use numbers up to one tile outside the rectangle,
+        // and those only use flags one tile further out.
+        if !solver::flags_consistent(&self.grid, min.offset(-1, -1), max.offset(1, 1)) {
+            return outcome;
+        }
+        let deductions = solver::deduce(&self.grid, min.offset(-1, -1), max.offset(1, 1));
+        for pos in deductions.safe {
+            let TilePos(x, y) = pos;
+            let in_rect = (x1..=x2).contains(&x) && (y1..=y2).contains(&y);
+            // An earlier reveal may have already uncovered this tile, and
+            // revealing a number would reveal its neighbors too.
+            if !in_rect || !matches!(self.grid.get_tile(pos), Tile::Covered(_, _)) {
+                continue;
+            }
+            let o = self.reveal(pos);
+            outcome.hit_mine |= o.hit_mine;
+            outcome.revealed.extend(o.revealed);
+        }
+        outcome
+    }
+
+    /// Returns a text drawing of a rectangle of the grid, including `min` and
+    /// `max`. See `Grid::from_ascii()` for the format.
+    pub fn dump_region(&self, min: TilePos, max: TilePos) -> String {
+        self.grid.dump_region(min, max, false)
+    }
+    /// Returns a practice game from a text drawing printed by the
+    /// `DumpVisible` key, which starts with a line `@x,y` giving the position
+    /// of the top left tile. The camera is centered on the drawing.
+    pub fn from_dump(s: &str) -> Result<Self, ParseError> {
+        let (first_line, drawing) = s.split_once('\n').unwrap_or((s, ""));
+        let top_left: TilePos = first_line.trim().trim_start_matches('@').parse()?;
+        let width = drawing
+            .lines()
+            .map(|line| line.trim_end_matches('\r').chars().count())
+            .max();
+        let height = drawing.lines().count();
+
+        let mut ret = Self::practice();
+        let track_opened_by = ret.grid.tracks_opened_by();
+        ret.grid = Grid::from_ascii(drawing, top_left)?;
+        ret.grid.set_track_opened_by(track_opened_by);
+        ret.has_revealed = ret.grid.any_revealed();
+        ret.camera_target.set_center(Point2::new(
+            top_left.0 as f64 + width.unwrap_or(0) as f64 / 2.0,
+            top_left.1 as f64 + 1.0 - height as f64 / 2.0,
+        ));
+        ret.camera = ret.camera_target;
+        Ok(ret)
+    }
+
+    /// Starts recording actions, discarding any recording already in
+    /// progress.
+    ///
+    /// The recording can only be replayed accurately if it is started on a new
+    /// grid.
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(replay::Recorder::new(self.grid.seed(), self.elapsed));
+    }
+    /// Stops recording actions and returns the recording, or `None` if there
+    /// was no recording in progress.
+    pub fn stop_recording(&mut self) -> Option<Replay> {
+        self.recorder.take().map(replay::Recorder::finish)
+    }
+    fn record(&mut self, action: Action) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(action, self.elapsed);
+        }
+    }
+    /// Starts a new grid with the seed of a replay and plays back its actions
+    /// at a speed multiplier, which may be `f64::INFINITY` to perform all
+    /// actions immediately.
+    pub fn replay(&mut self, replay: Replay, speed: f64) {
+        self.grid = Grid::with_seed(replay.seed);
+        self.undo_history.clear();
+        self.state = GameState::Playing;
+        self.has_revealed = false;
+        self.playback = Some(replay::Playback::new(replay, speed));
+        self.advance_playback(Duration::ZERO);
+    }
+    fn advance_playback(&mut self, duration: Duration) {
+        if let Some(playback) = &mut self.playback {
+            let actions = playback.advance(duration);
+            if playback.is_done() {
+                self.playback = None;
+            }
+            for action in actions {
+                self.do_action(action);
+            }
+        }
+    }
+    /// Makes the visible tiles the objective zone, or removes the objective
+    /// zone if there already is one.
+    fn toggle_objective_zone(&mut self) {
+        match self.objective_zone() {
+            Some(_) => {
+                self.clear_objective_zone();
+                eprintln!("Removed objective");
+            }
+            None => {
+                let (min, max) = self.camera.visible_tile_rect();
+                self.set_objective_zone(min, max);
+                eprintln!("Objective: solve every square from {} to {}", min, max);
+            }
+        }
+    }
+    fn toggle_recording(&mut self) {
+        match self.stop_recording() {
+            None => {
+                self.start_recording();
+                eprintln!("Started recording");
+            }
+            Some(replay) => match Self::get_file_path(REPLAY_FILE_NAME) {
+                Some(path) if std::fs::write(&path, replay.to_string()).is_ok() => {
+                    eprintln!("Saved recording to {}", path.display());
+                }
+                _ => eprintln!("Failed to save recording"),
+            },
+        }
+    }
 
     /// Updates camera according to a drag.
-    pub fn update_camera_for_drag(cam: &mut Camera, drag: input::Drag) {
+    pub fn update_camera_for_drag(cam: &mut Camera, drag: &input::Drag, settings: &Settings) {
         if drag.past_threshold {
             match drag.kind {
                 input::DragKind::Pan => {
@@ -81,16 +1012,53 @@ impl Game {
                     cam.set_center(new_center);
                 }
                 input::DragKind::Scale => {
-                    let y1 = drag.cursor_start.1 as f64;
-                    let y2 = drag.cursor_end.1 as f64;
-                    let delta = (y2 - y1) / -camera::PIXELS_PER_2X_SCALE;
+                    let delta = Self::drag_scale_delta(drag, settings);
                     let initial = Scale::from_factor(drag.initial_scale_factor);
                     let new_scale = Scale::from_log2_factor(initial.log2_factor() + delta);
                     cam.set_scale(new_scale);
                 }
+                input::DragKind::Flag => (),
             }
         }
     }
+    /// Returns the base-2 logarithm of the factor to scale by for a scale
+    /// drag.
+    fn drag_scale_delta(drag: &input::Drag, settings: &Settings) -> f64 {
+        let y1 = drag.cursor_start.1 as f64;
+        let y2 = drag.cursor_end.1 as f64;
+        let delta = (y2 - y1) / -settings.drag_scale_pixels_per_2x;
+        if settings.invert_drag_scale {
+            -delta
+        } else {
+            delta
+        }
+    }
+
+    /// Returns the direction to pan when the cursor is near the edge of the
+    /// window. Each component ranges from -1.0 at the very edge to 0.0 at the
+    /// margin.
+    fn edge_pan_direction(
+        (x, y): (u32, u32),
+        (w, h): (u32, u32),
+        settings: &Settings,
+    ) -> Vector2<f64> {
+        let axis = |pos: u32, size: u32| {
+            let margin = std::cmp::min(settings.edge_pan_margin, size / 2) as f64;
+            // Measure from the center of the pixel.
+            let pos = pos as f64 + 0.5;
+            let size = size as f64;
+            if pos < margin {
+                -(margin - pos) / margin
+            } else if pos > size - margin {
+                (pos - (size - margin)) / margin
+            } else {
+                0.0
+            }
+        };
+        // Pixel Y coordinates increase downwards, but tile Y coordinates
+        // increase upwards.
+        Vector2::new(axis(x, w), -axis(y, h))
+    }
 
     pub fn handle_event(&mut self, ev: WindowEvent<'_>) {
         match ev {
@@ -108,20 +1076,17 @@ impl Game {
             WindowEvent::ModifiersChanged(modifiers_state) => {
                 self.modifiers = modifiers_state;
             }
+            // Key releases are not received while the window is unfocused, so
+            // forget about any keys that are held.
+            WindowEvent::Focused(false) => {
+                self.keys = input::KeysPressed::default();
+                self.held_toggle_keys.clear();
+                self.modifiers = ModifiersState::empty();
+            }
 
             // Handle cursor events.
             WindowEvent::CursorMoved { position, .. } => {
-                let pos = (position.x as u32, position.y as u32);
-                // Update cursor position.
-                self.cursor_pos = Some(pos);
-                // Update drag in progress.
-                if let Some(d) = &mut self.drag {
-                    d.update_cursor_end(pos);
-                    if d.past_threshold {
-                        Self::update_camera_for_drag(&mut self.camera, *d);
-                        Self::update_camera_for_drag(&mut self.camera_target, *d);
-                    }
-                }
+                self.handle_cursor_moved((position.x as u32, position.y as u32));
             }
             WindowEvent::CursorLeft { .. } => self.cursor_pos = None,
 
@@ -139,11 +1104,123 @@ impl Game {
     }
 
     fn handle_key_press(&mut self, _sc: ScanCode, vkc: Option<VirtualKeyCode>) {
-        if vkc == Some(VirtualKeyCode::S) && self.modifiers == ModifiersState::CTRL {
-            self.save_to_file();
+        let action = vkc.and_then(|key| self.keybindings.action_for_key(key, self.modifiers));
+        if action != Some(KeyAction::Reveal) {
+            self.pending_reveal = None;
+        }
+        if let (Some(action), Some(key)) = (action, vkc) {
+            // Ignore repeated key presses while the key is held.
+            if action.is_toggle() && !self.held_toggle_keys.insert(key) {
+                return;
+            }
+        }
+        match action {
+            Some(KeyAction::Reveal) => {
+                if let Some(tile_pos) = self.cursor_tile_pos() {
+                    self.reveal_with_confirmation(tile_pos);
+                }
+            }
+            Some(KeyAction::SweepVisible) => {
+                self.sweep_visible();
+            }
+            Some(KeyAction::Undo) => {
+                self.undo();
+            }
+            Some(KeyAction::ClearFlags) => self.clear_flags(),
+            Some(KeyAction::Retry) => self.retry(),
+            Some(KeyAction::NewGame) => {
+                self.new_random();
+                eprintln!("Started a new board with seed {}", self.seed());
+            }
+            Some(KeyAction::ZoomToFit) => self.zoom_to_fit(),
+            Some(KeyAction::ShowHelp) => {
+                for line in self.binding_help_lines() {
+                    eprintln!("{}", line);
+                }
+            }
+            Some(KeyAction::ToggleAutoplay) => self.autoplay = !self.autoplay,
+            Some(KeyAction::CyclePalette) => self.settings.palette = self.settings.palette.next(),
+            Some(KeyAction::Brighten) => self
+                .settings
+                .set_brightness(self.settings.brightness() + Settings::BRIGHTNESS_STEP),
+            Some(KeyAction::Darken) => self
+                .settings
+                .set_brightness(self.settings.brightness() - Settings::BRIGHTNESS_STEP),
+            Some(KeyAction::Save) => self.save_to_file(),
+            Some(KeyAction::ToggleRecording) => self.toggle_recording(),
+            Some(KeyAction::DescribeTile) => {
+                if let Some(tile_pos) = self.cursor_tile_pos() {
+                    eprintln!("{}: {:?}", tile_pos, self.tile_info(tile_pos));
+                }
+            }
+            Some(KeyAction::DumpVisible) => {
+                let (min, max) = self.camera.visible_tile_rect();
+                eprintln!("@{}\n{}", TilePos(min.0, max.1), self.dump_region(min, max));
+            }
+            Some(KeyAction::ToggleObjectiveZone) => self.toggle_objective_zone(),
+            Some(KeyAction::VerifySave) => {
+                if self.verify_roundtrip() {
+                    eprintln!("Game saves and loads correctly");
+                } else {
+                    eprintln!("Game does not save and load correctly");
+                }
+            }
+            None => (),
+        }
+    }
+    /// Returns one line for each keybinding, describing the key and what it
+    /// does.
+    pub fn binding_help_lines(&self) -> Vec<String> {
+        self.keybindings.help_lines()
+    }
+    /// Returns the keys that trigger actions.
+    ///
+    /// Nothing uses this yet; it is for a settings screen that lists the
+    /// current keybindings.
+    #[allow(dead_code)]
+    pub fn keybindings(&self) -> &KeyBindings {
+        &self.keybindings
+    }
+    /// Binds a key to an action, replacing its previous binding, unless
+    /// another action is already bound to the same key.
+    ///
+    /// Nothing rebinds keys yet; this is for a settings screen.
+    #[allow(dead_code)]
+    pub fn set_keybinding(
+        &mut self,
+        action: KeyAction,
+        binding: KeyBinding,
+    ) -> Result<(), KeyConflict> {
+        self.keybindings.try_set(action, binding)
+    }
+    fn handle_key_release(&mut self, _sc: ScanCode, vkc: Option<VirtualKeyCode>) {
+        if let Some(key) = vkc {
+            self.held_toggle_keys.remove(&key);
+        }
+    }
+
+    fn handle_cursor_moved(&mut self, pos: (u32, u32)) {
+        // Update cursor position.
+        self.cursor_pos = Some(pos);
+        // Update drag in progress.
+        if let Some(d) = &mut self.drag {
+            let prev_pos = d.cursor_end;
+            d.update_cursor_end(pos);
+            if d.kind == input::DragKind::Flag {
+                let start = self.camera.pixel_to_tile_pos(prev_pos);
+                let end = self.camera.pixel_to_tile_pos(pos);
+                let to_flag = input::tiles_along_line(start, end)
+                    .filter(|&tile_pos| d.flagged_tiles.insert(tile_pos))
+                    .collect::<Vec<_>>();
+                for tile_pos in to_flag {
+                    self.flag(tile_pos);
+                }
+            } else if d.past_threshold {
+                Self::update_camera_for_drag(&mut self.camera, d, &self.settings);
+                Self::update_camera_for_drag(&mut self.camera_target, d, &self.settings);
+            }
         }
     }
-    fn handle_key_release(&mut self, _sc: ScanCode, _vkc: Option<VirtualKeyCode>) {}
 
     fn handle_mouse_wheel(&mut self, delta: MouseScrollDelta) {
         let dy = match delta {
@@ -151,10 +1228,11 @@ impl Game {
             MouseScrollDelta::PixelDelta(delta) => delta.y,
         };
 
-        let invariant_pos = if let Some(pixel) = self.cursor_pos {
-            Some(self.camera.pixel_to_tile_coords(pixel))
-        } else {
-            None
+        let invariant_pos = match self.cursor_pos {
+            Some(pixel) if self.settings.wheel_zoom_around_cursor => {
+                Some(self.camera.pixel_to_tile_coords(pixel))
+            }
+            _ => None,
         };
 
         if !self.is_drag_scaling() {
@@ -173,6 +1251,7 @@ impl Game {
         };
 
         let drag_kind = match button {
+            MouseButton::Right if self.modifiers.shift() => input::DragKind::Flag,
             MouseButton::Left | MouseButton::Right => input::DragKind::Pan,
             MouseButton::Middle => input::DragKind::Scale,
             _ => return,
@@ -188,18 +1267,27 @@ impl Game {
             past_threshold: false,
 
             kind: drag_kind,
+            flagged_tiles: HashSet::new(),
         });
+
+        if drag_kind == input::DragKind::Flag {
+            // Flag the tile under the cursor immediately.
+            self.handle_cursor_moved(pixel);
+        } else {
+            // Don't fight any camera motion already in progress.
+            self.stop_camera();
+        }
     }
     fn handle_mouse_release(&mut self, button: MouseButton) {
-        let tile_pos = match self.cursor_pos {
-            Some(pixel) => self.camera.pixel_to_tile_pos(pixel),
+        let tile_pos = match self.cursor_tile_pos() {
+            Some(pos) => pos,
             None => return,
         };
 
-        if let Some(d) = self.drag {
+        if let Some(d) = &self.drag {
             if button == d.button {
-                self.drag = None;
-                if d.past_threshold {
+                let d = self.drag.take().unwrap();
+                if d.past_threshold || d.kind == input::DragKind::Flag {
                     return;
                 }
             } else {
@@ -208,16 +1296,47 @@ impl Game {
         }
 
         match button {
-            MouseButton::Left => self.grid.reveal(tile_pos),
-            MouseButton::Right => self.grid.toggle_flag(tile_pos),
+            MouseButton::Left if self.modifiers.alt() => {
+                let TilePos(x, y) = tile_pos;
+                self.camera_target
+                    .set_center(Point2::new(x as f64 + 0.5, y as f64 + 0.5));
+            }
+            MouseButton::Left if self.modifiers.ctrl() => {
+                self.reveal_radius(tile_pos);
+            }
+            MouseButton::Left => self.reveal_with_confirmation(tile_pos),
+            MouseButton::Right => self.toggle_flag(tile_pos),
             MouseButton::Middle => (),
             MouseButton::Other(_) => (),
         }
     }
 
     pub fn do_frame(&mut self, frame_duration: Duration) {
+        if self.state == GameState::Playing {
+            self.elapsed += frame_duration;
+        }
+        self.animation_time += frame_duration;
+        let animation_time = self.animation_time;
+        self.flags_planted_at
+            .retain(|_, &mut planted_at| animation_time - planted_at < FLAG_PLANT_DURATION);
+        self.advance_playback(frame_duration);
+        self.chunks_generated_this_frame = 0;
+        if self.autoplay && (self.state != GameState::Playing || !self.autoplay_step()) {
+            self.autoplay = false;
+        }
+
         self.camera_target
             .set_target_dimensions(self.camera.target_dimensions());
+        self.camera
+            .set_half_pixel_offset(self.settings.half_pixel_offset);
+        self.camera_target
+            .set_half_pixel_offset(self.settings.half_pixel_offset);
+        self.camera.set_pixel_perfect(self.settings.pixel_perfect);
+        self.camera_target
+            .set_pixel_perfect(self.settings.pixel_perfect);
+        self.camera.set_render_offset(self.settings.render_offset);
+        self.camera_target
+            .set_render_offset(self.settings.render_offset);
 
         let mut dx = 0.0;
         let mut dy = 0.0;
@@ -238,24 +1357,59 @@ impl Game {
             }
         }
 
-        let pan_delta = Vector2::new(dx, dy) * input::KEYBD_MOVE_SPEED
-            / self.camera_target.scale().factor()
-            * frame_duration.as_secs_f64();
+        let mut pan_speed = Vector2::new(dx, dy) * input::KEYBD_MOVE_SPEED;
+        if let Some(cursor) = self.cursor_pos.filter(|_| self.settings.edge_pan) {
+            let target_dimensions = self.camera.target_dimensions();
+            pan_speed += Self::edge_pan_direction(cursor, target_dimensions, &self.settings)
+                * self.settings.edge_pan_speed;
+        }
+        let pan_delta =
+            pan_speed / self.camera_target.scale().factor() * frame_duration.as_secs_f64();
         self.camera_target.pan(pan_delta);
 
         let scale_delta = dz * input::KEYBD_SCALE_SPEED * frame_duration.as_secs_f64();
-        self.camera_target.scale_by_log2_factor(scale_delta, None);
+        let invariant_pos = match self.cursor_pos {
+            // Use the target camera, since the zoom accumulates there over
+            // many frames.
+            Some(pixel) if self.settings.keyboard_zoom_around_cursor => {
+                Some(self.camera_target.pixel_to_tile_coords(pixel))
+            }
+            _ => None,
+        };
+        self.camera_target
+            .scale_by_log2_factor(scale_delta, invariant_pos);
 
+        // Only the target is snapped, so the camera eases into the new scale
+        // instead of jumping to it.
         if dz == 0.0 && !self.is_drag_scaling() {
             self.camera_target.snap_scale(None);
         }
 
         self.camera
             .advance_interpolation(self.camera_target, frame_duration);
+
+        // Shaking only moves the image, not the tiles under the cursor.
+        let mut shake_pixels = Vector2::new(0.0, 0.0);
+        if let Some(time) = &mut self.shake_time {
+            *time += frame_duration;
+            shake_pixels = camera::shake_offset(*time, self.settings.shake_intensity);
+            if *time >= camera::SHAKE_DURATION {
+                self.shake_time = None;
+            }
+        }
+        self.camera
+            .set_shake_offset(shake_pixels / self.camera.scale().factor());
+    }
+
+    /// Returns the position of the tile under the mouse cursor, or `None` if
+    /// the cursor is not in the window.
+    pub fn cursor_tile_pos(&self) -> Option<TilePos> {
+        self.cursor_pos
+            .map(|pixel| self.camera.pixel_to_tile_pos(pixel))
     }
 
     fn is_drag_scaling(&self) -> bool {
-        if let Some(d) = self.drag {
+        if let Some(d) = &self.drag {
             d.kind == input::DragKind::Scale
         } else {
             false
@@ -263,33 +1417,1638 @@ impl Game {
     }
 
     pub fn save_to_file(&self) {
-        match self.try_save_to_file() {
-            Ok(()) => eprintln!(
-                "Saved game to {}",
-                Self::get_data_file_path().unwrap().display(),
-            ),
-            Err(()) => eprintln!("Failed to save game data"),
+        match (self.try_save_to_file(), &self.settings.save_file) {
+            (Ok(true), Some(path)) => eprintln!("Saved game to {}", path.display()),
+            (Ok(_), _) => eprintln!("Practice and arena games are not saved"),
+            (Err(()), _) => eprintln!("Failed to save game data"),
         }
     }
     pub fn load_from_file() -> Self {
-        Self::try_load_from_file().unwrap_or_else(|| {
+        let mut ret = Self::try_load_from_file().unwrap_or_else(|| {
             eprintln!("Unable to load existing game data; starting new game");
             Game::new()
-        })
+        });
+        ret.settings.save_file = Self::get_data_file_path();
+        ret
     }
 
-    pub fn try_save_to_file(&self) -> Result<(), ()> {
-        std::fs::write(Self::get_data_file_path().ok_or(())?, self.to_string()).map_err(|_| ())
+    /// Returns `true` if saving the game and loading it again gives back the
+    /// same grid, seed, and camera position. Anything that isn't saved, such
+    /// as settings or undo history, is ignored.
+    pub fn verify_roundtrip(&self) -> bool {
+        let loaded = match self.to_string().parse::<Game>() {
+            Ok(game) => game,
+            Err(_) => return false,
+        };
+        loaded.grid.content_hash() == self.grid.content_hash()
+            && loaded.grid.seed() == self.grid.seed()
+            && loaded.grid.flag_count() == self.grid.flag_count()
+            && loaded
+                .camera_target
+                .approx_eq(self.camera_target, camera::CAMERA_EPSILON)
+    }
+    /// Saves the game to the file in the `save_file` setting. Returns
+    /// `Ok(false)` without saving anything if there is no such file or the
+    /// game is in an arena.
+    pub fn try_save_to_file(&self) -> Result<bool, ()> {
+        match &self.settings.save_file {
+            Some(path) if self.grid.arena().is_none() => {
+                std::fs::write(path, self.to_string()).map_err(|_| ())?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
     }
     pub fn try_load_from_file() -> Option<Self> {
-        std::fs::read_to_string(Self::get_data_file_path()?)
+        match std::fs::read_to_string(Self::get_data_file_path()?)
             .ok()?
             .parse()
-            .ok()
+        {
+            Ok(game) => Some(game),
+            Err(e) => {
+                eprintln!("Failed to parse game data: {}", e);
+                None
+            }
+        }
     }
     fn get_data_file_path() -> Option<std::path::PathBuf> {
+        Self::get_file_path(SAVE_FILE_NAME)
+    }
+    /// Returns the default path to save statistics to, next to the saved
+    /// game.
+    pub fn default_stats_file_path() -> Option<std::path::PathBuf> {
+        Self::get_file_path(STATS_FILE_NAME)
+    }
+    /// Returns the default path to save best times to, next to the saved
+    /// game.
+    pub fn default_best_times_file_path() -> Option<std::path::PathBuf> {
+        Self::get_file_path(BEST_TIMES_FILE_NAME)
+    }
+    fn get_file_path(file_name: &str) -> Option<std::path::PathBuf> {
         let mut path = std::env::current_exe().ok()?.parent()?.to_path_buf();
-        path.push(SAVE_FILE_NAME);
+        path.push(file_name);
         Some(path)
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_space_reveals_cursor_tile() {
+    let mut game = Game::new();
+    game.camera.set_target_dimensions((100, 100));
+
+    // Cursor is off-window, so nothing happens.
+    game.handle_key_press(0, Some(VirtualKeyCode::Space));
+    assert!(game.grid.get_chunk(grid::ChunkPos(0, 0)).is_none());
+
+    // 16 pixels per tile; the center of the window is at (0, 0).
+    game.cursor_pos = Some((50 + 16 * 2 + 8, 50 - 16 * 3 + 8));
+    game.handle_key_press(0, Some(VirtualKeyCode::Space));
+    assert!(!matches!(
+        game.grid.get_tile(TilePos(2, 2)),
+        Tile::Covered(_, _)
+    ));
+}
+
+#[cfg(test)]
+#[test]
+fn test_flag_drag_toggles_once() {
+    let mut game = Game::new();
+    game.settings.question_marks_enabled = true;
+    game.camera.set_target_dimensions((100, 100));
+    game.modifiers = ModifiersState::SHIFT;
+
+    // Dragging only adds flags, even over tiles that are already marked.
+    game.toggle_flag(TilePos(1, 0));
+    game.toggle_flag(TilePos(2, 0));
+    game.toggle_flag(TilePos(2, 0));
+
+    // Drag back and forth over tiles (0, 0) through (2, 0), jittering over
+    // tile (1, 0) several times.
+    game.cursor_pos = Some((58, 42));
+    game.handle_mouse_press(MouseButton::Right);
+    for &x in &[60, 74, 76, 73, 77, 90, 75, 60] {
+        game.handle_cursor_moved((x, 42));
+    }
+    game.handle_mouse_release(MouseButton::Right);
+
+    for x in 0..=2 {
+        assert_eq!(
+            game.grid.get_tile(TilePos(x, 0)),
+            Tile::Covered(FlagState::Flag, HiddenState::Unknown),
+        );
+    }
+    assert_eq!(game.grid.get_tile(TilePos(3, 0)), Tile::default());
+    assert!(game.drag.is_none());
+}
+
+#[cfg(test)]
+#[test]
+fn test_window_title() {
+    let mut game = Game::new();
+    assert_eq!(game.window_title(), "Infinite Minesweeper");
+    game.state = GameState::Lost;
+    assert_eq!(game.window_title(), "Infinite Minesweeper — Game Over");
+    game.state = GameState::Won;
+    assert_eq!(game.window_title(), "Infinite Minesweeper — You Win!");
+
+    game = Game::beginner();
+    assert_eq!(game.window_title(), "Infinite Minesweeper — 10 mines left");
+    game.toggle_flag(TilePos(0, 0));
+    assert_eq!(game.window_title(), "Infinite Minesweeper — 9 mines left");
+    game.state = GameState::Won;
+    assert_eq!(game.window_title(), "Infinite Minesweeper — You Win!");
+    game.best_times
+        .record(Preset::Beginner, Duration::from_millis(12345));
+    assert_eq!(
+        game.window_title(),
+        "Infinite Minesweeper — You Win! Best beginner time: 12.345s",
+    );
+
+    game = Game::new();
+    game.grid = grid::test_grid_with_mines(&[TilePos(0, 0), TilePos(1, 0)]);
+    game.reveal(TilePos(1, 1));
+    assert_eq!(game.state, GameState::Playing);
+    game.reveal(TilePos(0, 0));
+    assert_eq!(game.state, GameState::Lost);
+}
+
+#[cfg(test)]
+#[test]
+fn test_record_and_replay() {
+    let mut game = Game::new();
+    game.grid = Grid::with_seed(5);
+    game.start_recording();
+    for &(x, y) in &[(0, 0), (10, -3), (-40, 25)] {
+        game.do_frame(Duration::from_millis(300));
+        game.toggle_flag(TilePos(x + 1, y));
+        game.reveal(TilePos(x, y));
+        game.reveal(TilePos(x, y));
+    }
+    let replay = game.stop_recording().unwrap();
+    assert_eq!(replay.actions.len(), 9);
+    assert_eq!(replay.actions[8].time, Duration::from_millis(900));
+
+    let mut replayed_game = Game::new();
+    replayed_game.replay(replay.to_string().parse().unwrap(), f64::INFINITY);
+    assert_eq!(replayed_game.grid, game.grid);
+    assert_eq!(replayed_game.state, game.state);
+
+    // Replay at normal speed.
+    let mut replayed_game = Game::new();
+    replayed_game.replay(replay, 1.0);
+    replayed_game.do_frame(Duration::from_millis(500));
+    assert!(replayed_game.grid != game.grid);
+    replayed_game.do_frame(Duration::from_millis(500));
+    assert_eq!(replayed_game.grid, game.grid);
+}
+
+#[cfg(test)]
+#[test]
+fn test_confirm_risky_reveal() {
+    let mut game = Game::new();
+    game.settings.confirm_risky_reveals = true;
+    game.grid = grid::test_grid_with_mines(&grid::test_square_border(TilePos(0, 0), TilePos(4, 4)));
+
+    // The first reveal is never risky.
+    game.reveal_with_confirmation(TilePos(2, 2));
+    assert_eq!(game.pending_reveal, None);
+    assert_eq!(game.grid.get_tile(TilePos(2, 2)), Tile::Number(0));
+    // Neither is a tile away from any number.
+    assert!(!game.is_risky_reveal(TilePos(10, 10)));
+
+    // Next to numbers, the first click arms.
+    game.reveal_with_confirmation(TilePos(0, 0));
+    assert_eq!(game.pending_reveal, Some(TilePos(0, 0)));
+    assert!(matches!(
+        game.grid.get_tile(TilePos(0, 0)),
+        Tile::Covered(_, _)
+    ));
+
+    // Any other action disarms.
+    game.toggle_flag(TilePos(1, 0));
+    game.reveal_with_confirmation(TilePos(0, 0));
+    assert_eq!(game.pending_reveal, Some(TilePos(0, 0)));
+    assert_eq!(game.state, GameState::Playing);
+
+    // The second click reveals.
+    game.reveal_with_confirmation(TilePos(0, 0));
+    assert_eq!(game.pending_reveal, None);
+    assert_eq!(game.state, GameState::Lost);
+
+    // A tile next to a number with the correct number of flags is not risky.
+    let mut game = Game::new();
+    game.settings.confirm_risky_reveals = true;
+    game.grid = grid::test_grid_with_mines(&grid::test_square_border(TilePos(0, 0), TilePos(4, 4)));
+    game.toggle_flag(TilePos(3, 3));
+    game.reveal(TilePos(2, 2));
+    game.toggle_flag(TilePos(3, 3));
+    assert!(!game.is_risky_reveal(TilePos(3, 3)));
+    game.reveal_with_confirmation(TilePos(3, 3));
+    assert_eq!(game.grid.get_tile(TilePos(3, 3)), Tile::Number(5));
+}
+
+#[cfg(test)]
+#[test]
+fn test_objective_zone() {
+    let mines = grid::test_square_border(TilePos(0, 0), TilePos(4, 4));
+    let zone_mines = [(0, 0), (1, 0), (2, 0), (0, 1), (0, 2)];
+
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.set_objective_zone(TilePos(0, 0), TilePos(2, 2));
+    game.reveal(TilePos(2, 2));
+    for &(x, y) in &zone_mines {
+        assert_eq!(game.state, GameState::Playing);
+        game.toggle_flag(TilePos(x, y));
+    }
+    assert_eq!(game.state, GameState::Won);
+
+    // A flag on a safe tile prevents it from being revealed.
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.set_objective_zone(TilePos(0, 0), TilePos(2, 2));
+    game.toggle_flag(TilePos(1, 1));
+    game.reveal(TilePos(2, 2));
+    for &(x, y) in &zone_mines {
+        game.toggle_flag(TilePos(x, y));
+    }
+    assert_eq!(game.state, GameState::Playing);
+    game.toggle_flag(TilePos(1, 1));
+    game.reveal(TilePos(1, 1));
+    assert_eq!(game.state, GameState::Won);
+
+    // Revealing a mine loses.
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.set_objective_zone(TilePos(0, 0), TilePos(2, 2));
+    game.reveal(TilePos(2, 2));
+    game.reveal(TilePos(0, 2));
+    assert_eq!(game.state, GameState::Lost);
+
+    // Mines in the zone are not placed until the first reveal, which is safe.
+    for seed in 0..20 {
+        let mut game = Game::new();
+        game.grid.set_seed(seed);
+        game.set_objective_zone(TilePos(0, 0), TilePos(9, 9));
+        assert_eq!(
+            game.grid.get_tile(TilePos(5, 5)),
+            Tile::Covered(FlagState::None, HiddenState::Unknown),
+        );
+        game.reveal(TilePos(5, 5));
+        assert_eq!(game.state, GameState::Playing);
+        assert!(matches!(
+            game.grid.get_tile(TilePos(0, 0)),
+            Tile::Covered(_, HiddenState::Mine | HiddenState::Safe) | Tile::Number(_),
+        ));
+    }
+
+    // The zone is saved.
+    let mut game = Game::new();
+    game.set_objective_zone(TilePos(-3, 0), TilePos(2, 5));
+    let loaded = game.to_string().parse::<Game>().unwrap();
+    assert_eq!(
+        loaded.objective_zone(),
+        Some((TilePos(-3, 0), TilePos(2, 5)))
+    );
+    game.clear_objective_zone();
+    let loaded = game.to_string().parse::<Game>().unwrap();
+    assert_eq!(loaded.objective_zone(), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_set_seed() {
+    let mines_in_chunk = |game: &mut Game| {
+        let chunk_pos = grid::ChunkPos(0, 0);
+        game.grid.place_mines_in_chunk(chunk_pos);
+        tiles_in_rect(chunk_pos.min_tile(), chunk_pos.max_tile())
+            .filter(|&pos| game.grid.get_tile(pos).is_mine())
+            .collect::<Vec<_>>()
+    };
+
+    // Setting the seed on a fresh game changes the board.
+    let mut a = Game::new();
+    a.set_seed(1);
+    assert_eq!(a.seed(), 1);
+    let mut b = Game::new();
+    b.set_seed(2);
+    let mines_a = mines_in_chunk(&mut a);
+    assert_ne!(mines_a, mines_in_chunk(&mut b));
+
+    // Setting the seed after mines are placed starts over.
+    b.reveal(TilePos(100, 100));
+    b.set_seed(1);
+    assert_eq!(b.seed(), 1);
+    assert_eq!(b.grid.chunk_count(), 0);
+    assert!(!b.has_revealed);
+    assert_eq!(b.state, GameState::Playing);
+    assert_eq!(mines_in_chunk(&mut b), mines_a);
+}
+
+#[cfg(test)]
+#[test]
+fn test_retry_and_new_random() {
+    fn play(game: &mut Game) {
+        game.reveal(TilePos(4, 4));
+        game.toggle_flag(TilePos(0, 0));
+        game.reveal(TilePos(8, 0));
+    }
+    fn check(mut game: Game) {
+        play(&mut game);
+        let seed = game.seed();
+        let hash = game.grid.content_hash();
+        game.do_frame(Duration::from_millis(100));
+
+        // Retrying gives back the same board.
+        game.retry();
+        assert_eq!(game.seed(), seed);
+        assert!(!game.grid.any_revealed());
+        assert_eq!(game.grid.flag_count(), 0);
+        assert_eq!(game.state, GameState::Playing);
+        assert_eq!(game.elapsed, Duration::default());
+        assert!(game.undo_history.is_empty());
+        play(&mut game);
+        assert_eq!(game.grid.content_hash(), hash);
+
+        // A new game gives a different board of the same kind.
+        let arena = game.grid.arena();
+        let arena_mine_count = game.grid.arena_mine_count();
+        game.new_random();
+        assert_ne!(game.seed(), seed);
+        assert!(!game.grid.any_revealed());
+        assert_eq!(game.grid.arena(), arena);
+        assert_eq!(game.grid.arena_mine_count(), arena_mine_count);
+        play(&mut game);
+        assert_ne!(game.grid.content_hash(), hash);
+    }
+
+    check(Game::new());
+    check(Game::beginner());
+}
+
+#[cfg(test)]
+#[test]
+fn test_first_reveal() {
+    let mut mines = grid::test_square_border(TilePos(-4, -4), TilePos(4, 4));
+    mines.extend_from_slice(&[TilePos(0, 0), TilePos(1, 1), TilePos(-1, 0)]);
+
+    // The first tile revealed is never a mine.
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    assert!(!game.reveal(TilePos(0, 0)).hit_mine);
+    assert!(matches!(game.grid.get_tile(TilePos(0, 0)), Tile::Number(n) if n >= 2));
+    assert!(game.reveal(TilePos(1, 1)).hit_mine);
+
+    // In big-open mode, the first tile revealed is always a zero.
+    let mut game = Game::new();
+    game.settings.first_reveal = FirstReveal::Opening;
+    game.grid = grid::test_grid_with_mines(&mines);
+    let outcome = game.reveal(TilePos(0, 0));
+    assert!(!outcome.hit_mine);
+    assert_eq!(game.grid.get_tile(TilePos(0, 0)), Tile::Number(0));
+    for nbr in TilePos(0, 0).neighbors() {
+        assert!(outcome.revealed.contains(&nbr));
+    }
+    assert_eq!(game.state, GameState::Playing);
+}
+
+#[cfg(test)]
+#[test]
+fn test_save_versions() {
+    let grid = "@0,-1\n".to_owned() + &Chunk::default().to_string().replace('`', " ") + "\n";
+    let v1 = format!("1.5,-2*\n\n{}", grid);
+    let v2 = format!("v2\n12345\n1.5,-2*\n\n{}", grid);
+    let v1_game = v1.parse::<Game>().unwrap();
+    let v2_game = v2.parse::<Game>().unwrap();
+    assert_eq!(v1_game.grid.content_hash(), v2_game.grid.content_hash());
+    assert_eq!(v1_game.grid.get_tile(TilePos(3, -64)), Tile::Number(0));
+    assert_eq!(v1_game.camera_target, v2_game.camera_target);
+    assert_eq!(v1_game.camera_target.center(), Point2::new(1.5, -2.0));
+    assert_eq!(v2_game.grid.seed(), 12345);
+
+    // Saving always writes the latest version.
+    assert_eq!(v2_game.to_string(), v2.replacen("v2", "v4", 1));
+    assert!(v1_game.to_string().starts_with("v4\n"));
+
+    let v3 = format!("v3\n12345\n1.5,-2,5*\n\n{}", grid);
+    let v3_game = v3.parse::<Game>().unwrap();
+    assert_eq!(v3_game.camera_target.scale().factor(), 32.0);
+    assert_eq!(v3_game.to_string(), v3.replacen("v3", "v4", 1));
+
+    let v4 = format!("v4\n12345\nzone -1,-2 3,4\n1.5,-2,5*\n\n{}", grid);
+    let v4_game = v4.parse::<Game>().unwrap();
+    assert_eq!(
+        v4_game.objective_zone(),
+        Some((TilePos(-1, -2), TilePos(3, 4))),
+    );
+    assert_eq!(v4_game.to_string(), v4);
+    assert_eq!(
+        "v4\n12345\nzone -1,-2\n0,0*\n".parse::<Game>().unwrap_err(),
+        ParseError::InvalidCoordinates("-1,-2".to_owned()),
+    );
+
+    assert_eq!(
+        "v5\n0,0*\n".parse::<Game>().unwrap_err(),
+        ParseError::UnknownVersion("v5".to_owned()),
+    );
+    assert_eq!(
+        "v2\n-1\n0,0*\n".parse::<Game>().unwrap_err(),
+        ParseError::InvalidSeed("-1".to_owned()),
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_error() {
+    let mut game = Game::new();
+    game.grid.set_tile(TilePos(3, 4), Tile::Number(2));
+    let s = game.to_string();
+    let loaded_game = s.parse::<Game>().unwrap();
+    assert_eq!(loaded_game.grid.get_tile(TilePos(3, 4)), Tile::Number(2));
+
+    assert_eq!(
+        "".parse::<Game>().unwrap_err(),
+        ParseError::MissingCameraPosition,
+    );
+    assert_eq!(
+        "1.5*\n".parse::<Game>().unwrap_err(),
+        ParseError::InvalidCoordinates("1.5".to_owned()),
+    );
+    assert_eq!(
+        "1.5,abc*\n".parse::<Game>().unwrap_err(),
+        ParseError::InvalidCoordinates("1.5,abc".to_owned()),
+    );
+    assert_eq!(
+        "0,0*\n@0,0\n:  ;\n.".parse::<Game>().unwrap_err(),
+        ParseError::InvalidGrid,
+    );
+    assert_eq!(
+        s.replace("@0,0", "@0;0").parse::<Game>().unwrap_err(),
+        ParseError::InvalidCoordinates("0;0".to_owned()),
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_stop_camera() {
+    let mut game = Game::new();
+    game.camera.set_target_dimensions((100, 100));
+    game.camera_target.pan(Vector2::new(50.0, -20.0));
+    assert!(!game.camera_settled());
+
+    game.do_frame(Duration::from_millis(10));
+    assert!(!game.camera_settled());
+    let live_camera = game.camera;
+    assert_ne!(live_camera.center(), Point2::new(0.0, 0.0));
+
+    game.stop_camera();
+    assert!(game.camera_settled());
+    assert_eq!(game.camera_target().center(), live_camera.center());
+    game.do_frame(Duration::from_millis(10));
+    assert_eq!(game.camera.center(), live_camera.center());
+}
+
+#[cfg(test)]
+#[test]
+fn test_camera_settled_despite_float_error() {
+    let mut game = Game::new();
+    let mut center = game.camera.center();
+    center.x += 1e-12;
+    game.camera.set_center(center);
+    assert!(game.camera_settled());
+    assert!(game.is_idle(false));
+}
+
+#[cfg(test)]
+#[test]
+fn test_reveal_radius() {
+    // Mines everywhere except for a few safe tiles.
+    let safe = [
+        TilePos(0, 0),
+        TilePos(1, 0),
+        TilePos(-1, 0),
+        TilePos(-2, 0),
+        TilePos(2, 2),
+        TilePos(3, 2),
+    ];
+    let mines: Vec<TilePos> = grid::tiles_in_rect(TilePos(-5, -5), TilePos(6, 6))
+        .filter(|pos| !safe.contains(pos))
+        .collect();
+    let new_game = || {
+        let mut game = Game::new();
+        game.grid = grid::test_grid_with_mines(&mines);
+        game.has_revealed = true;
+        game.reveal(TilePos(0, 0));
+        game.reveal(TilePos(2, 2));
+        game
+    };
+    let flag_mines_around = |game: &mut Game, pos: TilePos| {
+        for nbr in pos.neighbors() {
+            if !safe.contains(&nbr) && !game.grid.get_tile(nbr).is_flagged() {
+                game.toggle_flag(nbr);
+            }
+        }
+    };
+
+    // (1, 0) and (-1, 0) can be deduced to be safe. (3, 2) can also be
+    // deduced to be safe, but it is too far away. Nothing is known about
+    // (-2, 0).
+    let mut game = new_game();
+    flag_mines_around(&mut game, TilePos(0, 0));
+    flag_mines_around(&mut game, TilePos(2, 2));
+    let mut outcome = game.reveal_radius(TilePos(-1, 0));
+    outcome.revealed.sort_by_key(|&TilePos(x, y)| (x, y));
+    assert_eq!(outcome.revealed, [TilePos(-1, 0), TilePos(1, 0)]);
+    assert!(!outcome.hit_mine);
+    for &pos in &[TilePos(-2, 0), TilePos(3, 2)] {
+        assert_eq!(
+            game.grid.get_tile(pos),
+            Tile::Covered(FlagState::None, HiddenState::Safe),
+        );
+    }
+
+    // A number with too many flags around it means that some flag is wrong,
+    // so nothing is revealed.
+    let mut game = new_game();
+    game.toggle_flag(TilePos(1, 0));
+    flag_mines_around(&mut game, TilePos(0, 0));
+    let outcome = game.reveal_radius(TilePos(0, 0));
+    assert_eq!(outcome, RevealOutcome::default());
+    assert_eq!(
+        game.grid.get_tile(TilePos(-1, 0)),
+        Tile::Covered(FlagState::None, HiddenState::Safe)
+    );
+    assert_eq!(game.state, GameState::Playing);
+
+    // A wrong flag that doesn't contradict any number is trusted, just like
+    // when chording.
+    let mut game = new_game();
+    game.toggle_flag(TilePos(1, 0));
+    flag_mines_around(&mut game, TilePos(0, 0));
+    game.toggle_flag(TilePos(0, 1));
+    let outcome = game.reveal_radius(TilePos(0, 0));
+    assert!(outcome.hit_mine);
+}
+
+#[cfg(test)]
+#[test]
+fn test_undo() {
+    let mines = grid::test_square_border(TilePos(0, 0), TilePos(4, 4));
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    assert!(!game.undo());
+
+    // Moves are undone in reverse order.
+    game.reveal(TilePos(2, 2));
+    game.toggle_flag(TilePos(0, 0));
+    let flagged_hash = game.grid.content_hash();
+    game.toggle_flag(TilePos(1, 0));
+    assert!(game.undo());
+    assert_eq!(game.grid.content_hash(), flagged_hash);
+    assert!(game.undo());
+    assert!(game.undo());
+    assert_eq!(
+        game.grid.get_tile(TilePos(2, 2)),
+        Tile::Covered(FlagState::None, HiddenState::Safe),
+    );
+    assert!(!game.has_revealed);
+    assert!(!game.undo());
+
+    // A loss is final.
+    game.reveal(TilePos(2, 2));
+    let before_loss = game.grid.content_hash();
+    assert!(game.reveal(TilePos(0, 0)).hit_mine);
+    assert_eq!(game.state, GameState::Lost);
+    assert!(!game.undo());
+    assert_eq!(game.state, GameState::Lost);
+    // The board can't be changed after a loss.
+    let lost_hash = game.grid.content_hash();
+    assert_eq!(game.reveal(TilePos(4, 4)), RevealOutcome::default());
+    game.toggle_flag(TilePos(4, 0));
+    assert_eq!(game.grid.content_hash(), lost_hash);
+
+    // In practice mode, undoing the fatal move continues the game.
+    game.settings.practice_mode = true;
+    assert!(game.undo());
+    assert_eq!(game.state, GameState::Playing);
+    assert_eq!(game.grid.content_hash(), before_loss);
+    assert_eq!(
+        game.grid.get_tile(TilePos(0, 0)),
+        Tile::Covered(FlagState::None, HiddenState::Mine),
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_clear_flags() {
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&[TilePos(0, 0), TilePos(2, 0)]);
+    game.start_recording();
+    game.reveal(TilePos(1, 1));
+    game.toggle_flag(TilePos(0, 0));
+    game.toggle_flag(TilePos(5, 5));
+    game.clear_flags();
+    assert_eq!(game.grid.flag_count(), 0);
+    assert!(game.flag_plant_ages().is_empty());
+    assert_eq!(game.grid.get_tile(TilePos(1, 1)), Tile::Number(2));
+
+    game.undo();
+    assert_eq!(game.grid.flag_count(), 2);
+    assert!(game.grid.get_tile(TilePos(5, 5)).is_flagged());
+
+    // Clearing flags is replayed.
+    game.clear_flags();
+    let replay = game.stop_recording().unwrap();
+    assert_eq!(replay.actions.last().unwrap().action, Action::ClearFlags);
+    let mut replayed_game = Game::new();
+    replayed_game.replay(replay, f64::INFINITY);
+    assert_eq!(replayed_game.grid.flag_count(), 0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_tile_info() {
+    let mines = grid::test_square_border(TilePos(0, 0), TilePos(4, 4));
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.reveal(TilePos(2, 2));
+    let chunk_count = game.grid.chunk_count();
+
+    // (1, 1) has 5 mines around it, of which 4 are flagged.
+    for &pos in &[TilePos(0, 0), TilePos(1, 0), TilePos(2, 0), TilePos(0, 1)] {
+        game.toggle_flag(pos);
+    }
+    let info = game.tile_info(TilePos(1, 1));
+    assert_eq!(info.tile, Tile::Number(5));
+    assert_eq!(info.flagged_neighbors, 4);
+    assert_eq!(info.covered_neighbors, 1);
+    assert!(!info.chord_available);
+
+    game.toggle_flag(TilePos(0, 2));
+    let info = game.tile_info(TilePos(1, 1));
+    assert_eq!(info.flagged_neighbors, 5);
+    assert_eq!(info.covered_neighbors, 0);
+    // There is nothing left to reveal.
+    assert!(!info.chord_available);
+    game.toggle_flag(TilePos(0, 2));
+
+    // Flagging (1, 2) instead satisfies (1, 1), leaving (0, 2) to reveal.
+    game.grid.set_tile(TilePos(1, 2), Tile::default());
+    game.toggle_flag(TilePos(1, 2));
+    let info = game.tile_info(TilePos(1, 1));
+    assert_eq!(info.flagged_neighbors, 5);
+    assert_eq!(info.covered_neighbors, 1);
+    assert!(info.chord_available);
+
+    // Changing the settings for question marks takes effect right away.
+    game.settings.question_marks_enabled = true;
+    game.toggle_flag(TilePos(1, 2));
+    assert!(!game.tile_info(TilePos(1, 1)).chord_available);
+    game.settings.question_marks_as_flags = true;
+    assert!(game.tile_info(TilePos(1, 1)).chord_available);
+
+    // Covered tiles don't show their hidden state.
+    assert_eq!(
+        game.tile_info(TilePos(0, 0)).tile,
+        Tile::Covered(FlagState::Flag, HiddenState::Unknown),
+    );
+    assert_eq!(game.tile_info(TilePos(500, 500)).tile, Tile::default());
+    assert_eq!(game.grid.chunk_count(), chunk_count);
+    assert_eq!(game.tile_info(TilePos(2, 2)).opened_by, None);
+
+    // Practice games show which click opened each tile.
+    let mut game = Game::practice();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.grid.set_track_opened_by(true);
+    game.reveal(TilePos(2, 2));
+    game.reveal(TilePos(10, 10));
+    let first = game.tile_info(TilePos(2, 2)).opened_by;
+    assert!(first.is_some());
+    assert_eq!(game.tile_info(TilePos(3, 3)).opened_by, first);
+    let second = game.tile_info(TilePos(10, 10)).opened_by;
+    assert!(second.is_some());
+    assert_ne!(second, first);
+    assert_eq!(game.tile_info(TilePos(0, 0)).opened_by, None);
+    game.undo();
+    assert_eq!(game.tile_info(TilePos(10, 10)).opened_by, None);
+    assert!(Game::practice().grid.tracks_opened_by());
+    assert!(!Game::new().grid.tracks_opened_by());
+}
+
+#[cfg(test)]
+#[test]
+fn test_stats_file() {
+    let path = std::env::temp_dir().join(format!(
+        "infinite_minesweeper_test_stats_{}.jsonl",
+        std::process::id(),
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let mines = grid::test_square_border(TilePos(0, 0), TilePos(4, 4));
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.settings.stats_file = Some(path.clone());
+    game.reveal(TilePos(2, 2));
+    assert!(!path.exists());
+
+    // Only the move that ends the game saves statistics.
+    game.reveal(TilePos(0, 0));
+    game.reveal(TilePos(4, 4));
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(contents.lines().count(), 1);
+    assert_eq!(game.stats().outcome, GameState::Lost);
+    assert_eq!(contents, format!("{}\n", game.stats().to_json()));
+    assert_eq!(game.stats().mine_density, MINE_DENSITY);
+
+    // Finite games have their own density.
+    assert_eq!(Game::beginner().stats().mine_density, 10.0 / 81.0);
+    assert_eq!(Game::expert().stats().mine_density, 99.0 / 480.0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_avoid_guessing() {
+    for seed in 0..5 {
+        let mut game = Game::beginner();
+        assert!(game.settings.avoid_guessing);
+        assert_eq!(game.settings.first_reveal, FirstReveal::Opening);
+        game.grid.set_seed(seed);
+        game.reveal(TilePos(4, 4));
+        let arena = game.grid.arena().unwrap();
+        assert_ne!(
+            solver::rate_difficulty(&game.grid, arena),
+            solver::Difficulty::RequiresGuess,
+        );
+        assert_eq!(game.grid.arena_mine_count(), Some(10));
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_best_time() {
+    let path = std::env::temp_dir().join(format!(
+        "infinite_minesweeper_test_best_times_{}.json",
+        std::process::id(),
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    // Wins a beginner game in one move, with the mines along two edges.
+    let win = |elapsed: Duration| {
+        let mut game = Game::beginner();
+        game.settings.best_times_file = Some(path.clone());
+        game.load_best_times();
+        game.has_revealed = true;
+        game.grid
+            .for_each_tile_in_rect(TilePos(0, 0), TilePos(8, 8), |TilePos(x, y), tile| {
+                let h = if y == 8 || (x, y) == (0, 7) {
+                    HiddenState::Mine
+                } else {
+                    HiddenState::Safe
+                };
+                *tile = Tile::Covered(FlagState::None, h);
+            });
+        game.elapsed = elapsed;
+        game.reveal(TilePos(8, 0));
+        assert_eq!(game.state, GameState::Won);
+        game
+    };
+
+    let game = win(Duration::from_secs(30));
+    assert_eq!(
+        game.best_time(Preset::Beginner),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(game.best_time(Preset::Intermediate), None);
+
+    // A slower win doesn't replace the best time.
+    let game = win(Duration::from_secs(40));
+    assert_eq!(
+        game.best_time(Preset::Beginner),
+        Some(Duration::from_secs(30))
+    );
+
+    // A faster win does, and it is saved.
+    let game = win(Duration::from_secs(20));
+    assert_eq!(
+        game.best_time(Preset::Beginner),
+        Some(Duration::from_secs(20))
+    );
+    let mut game = Game::new();
+    game.settings.best_times_file = Some(path.clone());
+    game.load_best_times();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        game.best_time(Preset::Beginner),
+        Some(Duration::from_secs(20))
+    );
+
+    // Losing, or winning a game that isn't a preset, doesn't count.
+    let mut game = Game::with_arena(9, 9, 10).unwrap();
+    game.settings.best_times_file = Some(path.clone());
+    game.end_game(GameState::Won);
+    let mut game = Game::beginner();
+    game.settings.best_times_file = Some(path.clone());
+    game.end_game(GameState::Lost);
+    assert_eq!(game.best_time(Preset::Beginner), None);
+    assert!(!path.exists());
+}
+
+#[cfg(test)]
+#[test]
+fn test_is_idle() {
+    let mut game = Game::new();
+    game.camera.set_target_dimensions((100, 100));
+    game.camera_target = game.camera;
+    assert!(game.is_idle(false));
+    assert!(!game.is_idle(true));
+
+    // The camera is moving.
+    game.camera_target.pan(Vector2::new(3.0, 0.0));
+    assert!(!game.is_idle(false));
+    game.stop_camera();
+    assert!(game.is_idle(false));
+
+    // Something changes every frame.
+    game.autoplay = true;
+    assert!(!game.is_idle(false));
+    game.autoplay = false;
+    game.settings.edge_pan = true;
+    game.handle_cursor_moved((50, 50));
+    assert!(game.is_idle(false));
+    game.handle_cursor_moved((0, 50));
+    assert!(!game.is_idle(false));
+    game.settings.edge_pan = false;
+    assert!(game.is_idle(false));
+
+    // Holding a modifier alone doesn't change anything.
+    #[allow(deprecated)]
+    let mut input = glium::glutin::event::KeyboardInput {
+        scancode: 42,
+        state: ElementState::Pressed,
+        virtual_keycode: Some(VirtualKeyCode::LShift),
+        modifiers: ModifiersState::SHIFT,
+    };
+    game.keys.update(&input);
+    game.modifiers = ModifiersState::SHIFT;
+    assert!(game.is_idle(false));
+    input.scancode = input::sc::W;
+    input.virtual_keycode = Some(VirtualKeyCode::W);
+    game.keys.update(&input);
+    assert!(!game.is_idle(false));
+}
+
+#[cfg(test)]
+#[test]
+fn test_idle_time_counts() {
+    let mut game = Game::new();
+    game.start_recording();
+    game.reveal(TilePos(0, 0));
+    game.do_frame(Duration::from_millis(16));
+
+    // No frames are drawn while idle, so the first frame after input spans the
+    // whole time spent idle, and it runs before the input is handled.
+    game.do_frame(Duration::from_secs(30));
+    game.toggle_flag(TilePos(100, 100));
+
+    let replay = game.stop_recording().unwrap();
+    let last_action = replay.actions.last().unwrap();
+    assert_eq!(last_action.time, Duration::from_millis(30_016));
+    assert_eq!(game.stats().elapsed, Duration::from_millis(30_016));
+}
+
+#[cfg(test)]
+#[test]
+fn test_flag_plant_ages() {
+    let mut game = Game::new();
+    game.toggle_flag(TilePos(1, 2));
+    game.do_frame(Duration::from_millis(50));
+    game.toggle_flag(TilePos(3, 4));
+    game.do_frame(Duration::from_millis(10));
+    let ages = game.flag_plant_ages();
+    assert_eq!(ages.len(), 2);
+    assert_eq!(ages[&TilePos(1, 2)], Duration::from_millis(60));
+    assert_eq!(ages[&TilePos(3, 4)], Duration::from_millis(10));
+    assert!(!game.is_idle(false));
+
+    // Removing a flag stops planting it.
+    game.toggle_flag(TilePos(3, 4));
+    assert_eq!(game.flag_plant_ages().len(), 1);
+    game.do_frame(Duration::from_millis(60));
+    assert!(game.flag_plant_ages().is_empty());
+    assert!(game.is_idle(false));
+}
+
+#[cfg(test)]
+#[test]
+fn test_shake_on_loss() {
+    let mines = grid::test_square_border(TilePos(0, 0), TilePos(4, 4));
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.settings.render_offset = Vector2::new(0.5, 0.0);
+    game.do_frame(Duration::from_millis(20));
+    let center = game.camera.center();
+    let still_matrix = game.camera.gl_matrix();
+    let mut without_offset = game.camera;
+    without_offset.set_render_offset(Vector2::new(0.0, 0.0));
+    assert_ne!(without_offset.gl_matrix(), still_matrix);
+    game.reveal(TilePos(2, 2));
+    game.reveal(TilePos(0, 0));
+    game.do_frame(Duration::from_millis(20));
+    assert_ne!(game.camera.gl_matrix(), still_matrix);
+    assert_eq!(game.camera.center(), center);
+    assert!(!game.is_idle(false));
+    for _ in 0..20 {
+        game.do_frame(Duration::from_millis(20));
+    }
+    // The shake settles exactly, and the render offset is kept.
+    assert_eq!(game.camera.gl_matrix(), still_matrix);
+    assert!(game.is_idle(false));
+}
+
+#[cfg(test)]
+#[test]
+fn test_flag_before_generation() {
+    let mut game = Game::new();
+    let flag_pos = TilePos(100, 100);
+    let chunk_pos = flag_pos.chunk();
+    game.toggle_flag(flag_pos);
+    assert_eq!(game.grid.generated_chunk_count(), 0);
+    assert!(!game.grid.get_chunk(chunk_pos).unwrap().all_mines_placed());
+    assert_eq!(
+        game.grid.get_tile(flag_pos),
+        Tile::Covered(FlagState::Flag, HiddenState::Unknown),
+    );
+
+    // The flag survives generation, and the first reveal is still safe.
+    let first_pos = TilePos(101, 101);
+    assert!(!game.reveal(first_pos).hit_mine);
+    assert!(game.grid.get_chunk(chunk_pos).unwrap().all_mines_placed());
+    assert!(matches!(
+        game.grid.get_tile(flag_pos),
+        Tile::Covered(FlagState::Flag, _),
+    ));
+}
+
+#[cfg(test)]
+#[test]
+fn test_practice_not_saved() {
+    let path = std::env::temp_dir().join("infinite_minesweeper_test_practice_not_saved.txt");
+    let _ = std::fs::remove_file(&path);
+    let mines = grid::test_square_border(TilePos(-2, -2), TilePos(2, 2));
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.reveal(TilePos(0, 0));
+
+    // A practice game is never saved.
+    let mut practice = Game::practice();
+    practice.grid = game.grid.clone();
+    assert!(practice.settings.save_file.is_none());
+    assert_eq!(practice.try_save_to_file(), Ok(false));
+
+    // Neither is a game in an arena.
+    let mut arena = Game::beginner();
+    arena.settings.save_file = Some(path.clone());
+    assert_eq!(arena.try_save_to_file(), Ok(false));
+    assert!(!path.exists());
+
+    game.settings.save_file = Some(path.clone());
+    assert_eq!(game.try_save_to_file(), Ok(true));
+    let saved = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(saved, game.to_string());
+}
+
+#[cfg(test)]
+#[test]
+fn test_verify_roundtrip() {
+    let mines = grid::test_square_border(TilePos(-3, -3), TilePos(3, 3));
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.reveal(TilePos(0, 0));
+    game.toggle_flag(TilePos(3, 3));
+    game.settings.question_marks_enabled = true;
+    game.toggle_flag(TilePos(-3, 3));
+    game.toggle_flag(TilePos(-3, 3));
+    game.camera_target.set_center(Point2::new(0.3, -7.25));
+    game.camera_target.set_scale(Scale::from_factor(40.0));
+    assert!(game.verify_roundtrip());
+}
+
+#[cfg(test)]
+#[test]
+fn test_first_reveal_opening_on_dense_board() {
+    let mines: Vec<_> = grid::tiles_in_rect(TilePos(-4, -4), TilePos(4, 4)).collect();
+    let mut game = Game::new();
+    game.settings.first_reveal = FirstReveal::Opening;
+    game.grid = grid::test_grid_with_mines(&mines);
+    let outcome = game.reveal(TilePos(0, 0));
+    assert!(!outcome.hit_mine);
+    for pos in TilePos(0, 0).neighbors() {
+        assert!(outcome.revealed.contains(&pos));
+        assert!(matches!(game.grid.get_tile(pos), Tile::Number(_)));
+    }
+    assert_eq!(game.state, GameState::Playing);
+}
+
+#[cfg(test)]
+#[test]
+fn test_difficulty_presets() {
+    for &(ref game, width, height, mines) in &[
+        (Game::beginner(), 9, 9, 10),
+        (Game::intermediate(), 16, 16, 40),
+        (Game::expert(), 30, 16, 99),
+    ] {
+        let arena = (TilePos(0, 0), TilePos(width - 1, height - 1));
+        assert_eq!(game.grid.arena(), Some(arena));
+        let mine_count = grid::tiles_in_rect(TilePos(-5, -5), TilePos(width + 5, height + 5))
+            .filter(|&pos| game.grid.get_tile(pos).is_mine())
+            .count();
+        assert_eq!(mine_count, mines);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_arena_first_reveal() {
+    for &first_reveal in &[FirstReveal::SafeTile, FirstReveal::Opening] {
+        for &pos in &[TilePos(0, 0), TilePos(4, 4), TilePos(8, 3)] {
+            let mut game = Game::beginner();
+            game.settings.first_reveal = first_reveal;
+            assert!(!game.reveal(pos).hit_mine);
+            let mine_count = grid::tiles_in_rect(TilePos(0, 0), TilePos(8, 8))
+                .filter(|&p| game.grid.get_tile(p).is_mine())
+                .count();
+            assert_eq!(mine_count, 10);
+            if first_reveal == FirstReveal::Opening {
+                assert_eq!(game.grid.get_tile(pos), Tile::Number(0));
+            }
+        }
+    }
+
+    // An arena too crowded for an opening still has a safe first tile.
+    let mut game = Game::with_arena(3, 3, 7).unwrap();
+    game.settings.first_reveal = FirstReveal::Opening;
+    assert!(!game.reveal(TilePos(1, 1)).hit_mine);
+    assert_eq!(game.grid.get_tile(TilePos(1, 1)), Tile::Number(7));
+    assert_eq!(game.grid.arena_mine_count(), Some(7));
+
+    assert_eq!(Game::with_arena(3, 3, 10).err(), Some(TooManyMines(9)));
+}
+
+#[cfg(test)]
+#[test]
+fn test_arena_win() {
+    // The last safe tile is surrounded by mines, so only revealing it directly
+    // can uncover it.
+    let mines = [TilePos(3, 2), TilePos(4, 2), TilePos(3, 3)];
+    let last = TilePos(4, 3);
+    let mut game = Game::with_arena(5, 4, mines.len()).unwrap();
+    game.has_revealed = true;
+    game.grid
+        .for_each_tile_in_rect(TilePos(0, 0), TilePos(4, 3), |pos, tile| {
+            let h = if mines.contains(&pos) {
+                HiddenState::Mine
+            } else {
+                HiddenState::Safe
+            };
+            *tile = Tile::Covered(FlagState::None, h);
+        });
+
+    let others: Vec<TilePos> = grid::tiles_in_rect(TilePos(0, 0), TilePos(4, 3))
+        .filter(|pos| !mines.contains(pos) && *pos != last)
+        .collect();
+    for pos in others {
+        assert!(!game.reveal(pos).hit_mine);
+    }
+    assert_eq!(game.state, GameState::Playing);
+    assert_eq!(game.grid.flag_count(), 0);
+    game.reveal(last);
+    assert_eq!(game.state, GameState::Won);
+
+    // Every mine is flagged.
+    for &pos in &mines {
+        assert_eq!(
+            game.grid.get_tile(pos),
+            Tile::Covered(FlagState::Flag, HiddenState::Mine),
+        );
+    }
+    assert_eq!(game.grid.flag_count(), mines.len());
+    assert_eq!(game.grid.mines_remaining(), 0);
+
+    // The timer stops.
+    let elapsed = game.elapsed;
+    game.do_frame(Duration::from_millis(100));
+    assert_eq!(game.elapsed, elapsed);
+}
+
+#[cfg(test)]
+#[test]
+fn test_binding_help_lines() {
+    let mut game = Game::new();
+    assert_eq!(
+        game.binding_help_lines(),
+        [
+            "Space: Uncover the square under the cursor",
+            "X: Uncover every visible square known to be safe",
+            "Ctrl+Z: Undo the last move",
+            "Delete: Remove every flag and question mark",
+            "F5: Start this board over",
+            "F2: Start a new board",
+            "Home: Zoom to show the whole board",
+            "?: Show controls",
+            "P: Start or stop solving automatically",
+            "C: Switch to the next color palette",
+            "]: Make squares brighter",
+            "[: Make squares darker",
+            "Ctrl+S: Save game",
+            "Ctrl+R: Start or stop recording a replay",
+            "F3: Print the visible squares as text",
+            "F4: Check that the game saves and loads correctly",
+            "F6: Print information about the square under the cursor",
+            "F7: Make the visible squares an objective to solve, or remove it",
+        ],
+    );
+
+    game.keybindings
+        .set(KeyAction::Reveal, KeyBinding::key(VirtualKeyCode::F));
+    assert_eq!(
+        game.binding_help_lines()[0],
+        "F: Uncover the square under the cursor",
+    );
+
+    // The remapped key reveals tiles, and the old one does nothing.
+    game.camera.set_target_dimensions((100, 100));
+    game.handle_cursor_moved((50, 50));
+    game.handle_key_press(0, Some(VirtualKeyCode::Space));
+    assert!(game.grid.get_chunk(grid::ChunkPos(0, 0)).is_none());
+    game.handle_key_press(0, Some(VirtualKeyCode::F));
+    assert!(matches!(game.grid.get_tile(TilePos(0, 0)), Tile::Number(_)));
+}
+
+#[cfg(test)]
+#[test]
+fn test_zoom_to_fit() {
+    let mut game = Game::beginner();
+    game.camera.set_target_dimensions((400, 300));
+    game.do_frame(Duration::ZERO);
+    game.zoom_to_fit();
+    assert_eq!(game.camera_target.center(), Point2::new(4.5, 4.5));
+    let (min, max) = game.camera_target.visible_tile_rect();
+    assert!(min.0 <= 0 && min.1 <= 0 && max.0 >= 8 && max.1 >= 8);
+
+    // An infinite game shows the chunks explored so far.
+    let mut game = Game::new();
+    game.camera.set_target_dimensions((400, 300));
+    game.do_frame(Duration::ZERO);
+    game.zoom_to_fit();
+    assert_eq!(game.camera_target, game.camera);
+    game.grid.place_mines_in_chunk(grid::ChunkPos(-1, 0));
+    game.grid.place_mines_in_chunk(grid::ChunkPos(0, 0));
+    game.zoom_to_fit();
+    assert_eq!(
+        game.camera_target.center(),
+        Point2::new(0.0, CHUNK_SIZE as f64 / 2.0),
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_brightness_keys() {
+    let mut game = Game::new();
+    game.handle_key_press(0, Some(VirtualKeyCode::RBracket));
+    assert_eq!(game.settings.brightness(), 1.0 + Settings::BRIGHTNESS_STEP);
+    for _ in 0..20 {
+        game.handle_key_press(0, Some(VirtualKeyCode::LBracket));
+    }
+    assert_eq!(game.settings.brightness(), Settings::MIN_BRIGHTNESS);
+}
+
+#[cfg(test)]
+#[test]
+fn test_set_keybinding() {
+    let mut game = Game::new();
+    let f = KeyBinding::key(VirtualKeyCode::F);
+    let ctrl_s = KeyBinding::ctrl(VirtualKeyCode::S);
+
+    // A free key can be bound, and the old key is freed.
+    assert_eq!(game.set_keybinding(KeyAction::Reveal, f), Ok(()));
+    assert_eq!(game.keybindings().get(KeyAction::Reveal), Some(f));
+    assert_eq!(
+        game.keybindings().action_for_binding(f),
+        Some(KeyAction::Reveal),
+    );
+    let space = KeyBinding::key(VirtualKeyCode::Space);
+    assert_eq!(game.keybindings().action_for_binding(space), None);
+
+    // A key used by another action can't be bound, but the same key with
+    // different modifiers can.
+    let err = game.set_keybinding(KeyAction::Reveal, ctrl_s).unwrap_err();
+    assert_eq!(err, KeyConflict(KeyAction::Save));
+    assert_eq!(err.to_string(), "Key is already used to save game");
+    assert_eq!(game.keybindings().get(KeyAction::Reveal), Some(f));
+    assert_eq!(game.keybindings().get(KeyAction::Save), Some(ctrl_s));
+    assert_eq!(
+        game.set_keybinding(KeyAction::Reveal, KeyBinding::key(VirtualKeyCode::S)),
+        Ok(()),
+    );
+
+    // Binding an action to its own key is not a conflict.
+    assert_eq!(game.set_keybinding(KeyAction::Save, ctrl_s), Ok(()));
+}
+
+#[cfg(test)]
+#[test]
+fn test_from_dump() {
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&[TilePos(0, 0)]);
+    game.reveal(TilePos(1, 1));
+    game.toggle_flag(TilePos(0, 0));
+    let dump = game.dump_region(TilePos(-2, -2), TilePos(2, 2));
+
+    let loaded = Game::from_dump(&format!("@-2,2\n{}", dump)).unwrap();
+    assert_eq!(loaded.dump_region(TilePos(-2, -2), TilePos(2, 2)), dump);
+    assert_eq!(loaded.camera_target.center(), Point2::new(0.5, 0.5));
+    assert!(loaded.settings.practice_mode);
+    assert!(loaded.grid.tracks_opened_by());
+    assert!(loaded.has_revealed);
+
+    assert_eq!(
+        Game::from_dump("@-2;2\n#").err(),
+        Some(ParseError::InvalidCoordinates("-2;2".to_owned())),
+    );
+    assert_eq!(
+        Game::from_dump("@0,0\n#x").err(),
+        Some(ParseError::InvalidTile('x')),
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_reveal_safe() {
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&[TilePos(0, 0)]);
+    game.has_revealed = true;
+    game.start_recording();
+    game.toggle_flag(TilePos(1, 1));
+    game.reveal(TilePos(5, 5));
+    let before = game.dump_region(TilePos(-2, -2), TilePos(2, 2));
+
+    // Chording the 1 next to the wrong flag would reveal the mine.
+    assert_eq!(
+        game.reveal_safe(TilePos(1, 0)),
+        Err(WouldHitMine(TilePos(0, 0)))
+    );
+    assert_eq!(game.dump_region(TilePos(-2, -2), TilePos(2, 2)), before);
+    assert_eq!(game.state, GameState::Playing);
+    assert_eq!(game.stop_recording().unwrap().actions.len(), 2);
+}
+
+#[cfg(test)]
+#[test]
+fn test_autoplay_step() {
+    // Box of mines with a gap in one side, inside a larger box of mines.
+    let mut mines = grid::test_square_border(TilePos(-2, -2), TilePos(8, 8));
+    mines.extend(grid::test_square_border(TilePos(0, 0), TilePos(6, 6)));
+    mines.retain(|&pos| pos != TilePos(6, 3));
+
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.camera.set_target_dimensions((400, 400));
+    game.camera.set_center(Point2::new(3.0, 3.0));
+    game.reveal(TilePos(3, 3));
+    // Question marks on mines are replaced with flags.
+    game.settings.question_marks_enabled = true;
+    game.toggle_flag(TilePos(0, 0));
+    game.toggle_flag(TilePos(0, 0));
+    assert!(game.autoplay_step());
+    assert_eq!(
+        game.grid.get_tile(TilePos(0, 0)),
+        Tile::Covered(FlagState::Flag, HiddenState::Mine)
+    );
+
+    let mut steps = 0;
+    while game.autoplay_step() {
+        steps += 1;
+        assert!(steps < 100, "autoplay did not halt");
+    }
+    assert_eq!(game.state, GameState::Playing);
+
+    // The inner box is solved completely.
+    for pos in grid::test_square_border(TilePos(0, 0), TilePos(6, 6)) {
+        if pos == TilePos(6, 3) {
+            assert_eq!(game.grid.get_tile(pos), Tile::Number(2));
+        } else {
+            assert_eq!(
+                game.grid.get_tile(pos),
+                Tile::Covered(FlagState::Flag, HiddenState::Mine)
+            );
+        }
+    }
+    // Outside the gap, the tiles that can be deduced are solved.
+    for y in 2..=4 {
+        assert!(matches!(game.grid.get_tile(TilePos(7, y)), Tile::Number(_)));
+        assert_eq!(
+            game.grid.get_tile(TilePos(8, y)),
+            Tile::Covered(FlagState::Flag, HiddenState::Mine)
+        );
+    }
+    // The rest is a guess.
+    for &pos in &[TilePos(7, 1), TilePos(8, 1), TilePos(7, 5), TilePos(8, 5)] {
+        assert!(matches!(
+            game.grid.get_tile(pos),
+            Tile::Covered(FlagState::None, _)
+        ));
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_sweep_visible() {
+    // Mines everywhere except for a few safe tiles.
+    let safe = [
+        TilePos(0, 0),
+        TilePos(1, 0),
+        TilePos(2, 0),
+        TilePos(5, 5),
+        TilePos(6, 5),
+    ];
+    let mines: Vec<TilePos> = grid::tiles_in_rect(TilePos(-3, -3), TilePos(8, 8))
+        .filter(|pos| !safe.contains(pos))
+        .collect();
+    let mut game = Game::new();
+    game.grid = grid::test_grid_with_mines(&mines);
+    game.has_revealed = true;
+    game.camera.set_target_dimensions((400, 400));
+
+    game.reveal(TilePos(0, 0));
+    game.reveal(TilePos(5, 5));
+    // With every mine around (0, 0) flagged, (1, 0) must be safe. Nothing is
+    // known about (6, 5).
+    for nbr in TilePos(0, 0).neighbors() {
+        if !safe.contains(&nbr) {
+            game.toggle_flag(nbr);
+        }
+    }
+
+    let outcome = game.sweep_visible();
+    assert_eq!(outcome.revealed, [TilePos(1, 0)]);
+    assert!(!outcome.hit_mine);
+    assert_eq!(game.grid.get_tile(TilePos(1, 0)), Tile::Number(6));
+    // (2, 0) is safe, but that could not be deduced before (1, 0) was revealed.
+    for &pos in &[TilePos(2, 0), TilePos(6, 5)] {
+        assert_eq!(
+            game.grid.get_tile(pos),
+            Tile::Covered(FlagState::None, HiddenState::Safe),
+        );
+    }
+    assert_eq!(game.state, GameState::Playing);
+}
+
+#[cfg(test)]
+#[test]
+fn test_autoplay_chunk_budget() {
+    let mut game = Game::new();
+    game.grid = Grid::with_seed(0);
+    game.settings.max_chunks_generated_per_frame = 1;
+    game.camera.set_target_dimensions((4000, 200));
+    game.camera.set_center(Point2::new(128.0, 32.0));
+    game.camera_target = game.camera;
+    // A zero in the middle of each of four chunks. The chunks exist, but no
+    // mines have been placed in them yet.
+    let zeros: Vec<TilePos> = (0..4).map(|i| TilePos(i * 64 + 32, 32)).collect();
+    for &pos in &zeros {
+        for nbr in pos.neighbors() {
+            game.grid
+                .set_tile(nbr, Tile::Covered(FlagState::None, HiddenState::Safe));
+        }
+        game.grid.set_tile(pos, Tile::Number(0));
+    }
+    game.has_revealed = true;
+    game.autoplay = true;
+
+    let mut frames = 0;
+    while game.autoplay {
+        let old_count = game.grid.generated_chunk_count();
+        game.do_frame(Duration::from_millis(16));
+        assert!(game.grid.generated_chunk_count() - old_count <= 1);
+        frames += 1;
+        assert!(frames < 1000, "autoplay did not halt");
+    }
+    assert!(frames >= 4);
+    for &pos in &zeros {
+        for nbr in pos.neighbors() {
+            assert!(matches!(game.grid.get_tile(nbr), Tile::Number(_)));
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_scale_snap_eases() {
+    let mut game = Game::new();
+    game.camera.set_scale(Scale::from_factor(24.0));
+    game.camera_target = game.camera;
+
+    game.do_frame(Duration::from_millis(16));
+    assert_eq!(game.camera_target().scale(), Scale::from_factor(32.0));
+    let scale = game.camera.scale();
+    assert!(Scale::from_factor(24.0) < scale && scale < Scale::from_factor(32.0));
+
+    for _ in 0..100 {
+        game.do_frame(Duration::from_millis(16));
+    }
+    assert_eq!(game.camera.scale(), Scale::from_factor(32.0));
+}
+
+#[cfg(test)]
+#[test]
+fn test_wheel_zoom_around_center() {
+    let cursor = (350, 40);
+    let mut game = Game::new();
+    game.camera.set_target_dimensions((400, 300));
+    game.camera_target = game.camera;
+    game.cursor_pos = Some(cursor);
+
+    // By default, the tile under the cursor stays in place.
+    let tile_coords = game.camera.pixel_to_tile_coords(cursor);
+    game.handle_mouse_wheel(MouseScrollDelta::LineDelta(0.0, 1.0));
+    assert!(game.camera_target.scale() > Scale::default());
+    let delta = game.camera_target.pixel_to_tile_coords(cursor) - tile_coords;
+    assert!(delta.x.abs() < 1e-9 && delta.y.abs() < 1e-9);
+
+    // Otherwise, the tile at the center stays in place.
+    game.settings.wheel_zoom_around_cursor = false;
+    game.camera = game.camera_target;
+    let center = game.camera_target.center();
+    let scale = game.camera_target.scale();
+    game.handle_mouse_wheel(MouseScrollDelta::LineDelta(0.0, 1.0));
+    assert!(game.camera_target.scale() > scale);
+    assert_eq!(game.camera_target.center(), center);
+}
+
+#[cfg(test)]
+#[test]
+fn test_keyboard_zoom_around_cursor() {
+    use glium::glutin::event::KeyboardInput;
+
+    #[allow(deprecated)]
+    let input = KeyboardInput {
+        scancode: input::sc::Q,
+        state: ElementState::Pressed,
+        virtual_keycode: Some(VirtualKeyCode::Q),
+        modifiers: ModifiersState::empty(),
+    };
+
+    let mut game = Game::new();
+    game.settings.keyboard_zoom_around_cursor = true;
+    game.camera.set_target_dimensions((400, 300));
+    game.camera_target = game.camera;
+    game.keys.update(&input);
+
+    // Without a cursor, zoom around the center.
+    game.do_frame(Duration::from_millis(50));
+    assert!(game.camera_target.scale() > Scale::default());
+    assert_eq!(game.camera_target.center(), Point2::new(0.0, 0.0));
+
+    let cursor = (350, 40);
+    game.cursor_pos = Some(cursor);
+    let tile_coords = game.camera_target.pixel_to_tile_coords(cursor);
+    let scale = game.camera_target.scale();
+    game.do_frame(Duration::from_millis(50));
+    assert!(game.camera_target.scale() > scale);
+    let new_tile_coords = game.camera_target.pixel_to_tile_coords(cursor);
+    let delta = new_tile_coords - tile_coords;
+    assert!(delta.x.abs() < 1e-9 && delta.y.abs() < 1e-9);
+}
+
+#[cfg(test)]
+#[test]
+fn test_focus_lost_releases_keys() {
+    use glium::glutin::event::KeyboardInput;
+
+    #[allow(deprecated)]
+    let input = KeyboardInput {
+        scancode: input::sc::D,
+        state: ElementState::Pressed,
+        virtual_keycode: Some(VirtualKeyCode::D),
+        modifiers: ModifiersState::empty(),
+    };
+
+    let mut game = Game::new();
+    game.keys.update(&input);
+    game.modifiers = ModifiersState::SHIFT;
+    game.do_frame(Duration::from_millis(10));
+    let center = game.camera_target.center();
+    assert!(center.x > 0.0);
+
+    game.handle_event(WindowEvent::Focused(false));
+    assert_eq!(game.modifiers, ModifiersState::empty());
+    game.do_frame(Duration::from_millis(10));
+    assert_eq!(game.camera_target.center(), center);
+}
+
+#[cfg(test)]
+#[test]
+fn test_held_toggle_key_fires_once() {
+    let mut game = Game::new();
+    // Key repeat sends the key press many times while the key is held.
+    for _ in 0..5 {
+        game.handle_key_press(0, Some(VirtualKeyCode::P));
+        assert!(game.autoplay);
+    }
+    game.handle_key_release(0, Some(VirtualKeyCode::P));
+    assert!(game.autoplay);
+
+    game.handle_key_press(0, Some(VirtualKeyCode::P));
+    assert!(!game.autoplay);
+    game.handle_key_press(0, Some(VirtualKeyCode::P));
+    assert!(!game.autoplay);
+
+    // The release may be missed if the window loses focus.
+    game.handle_event(WindowEvent::Focused(false));
+    game.handle_key_press(0, Some(VirtualKeyCode::P));
+    assert!(game.autoplay);
+}
+
+#[cfg(test)]
+#[test]
+fn test_edge_pan_direction() {
+    let mut settings = Settings::default();
+    settings.edge_pan_margin = 20;
+    let dims = (400, 300);
+
+    let v = Game::edge_pan_direction((0, 0), dims, &settings);
+    assert_eq!(v, Vector2::new(-0.975, 0.975));
+    let v = Game::edge_pan_direction((399, 299), dims, &settings);
+    assert_eq!(v, Vector2::new(0.975, -0.975));
+    // Closer to the edge pans faster.
+    let v = Game::edge_pan_direction((390, 5), dims, &settings);
+    assert_eq!(v, Vector2::new(0.525, 0.725));
+    let v = Game::edge_pan_direction((200, 20), dims, &settings);
+    assert_eq!(v, Vector2::new(0.0, 0.0));
+
+    let mut game = Game::new();
+    game.settings = settings;
+    game.camera.set_target_dimensions(dims);
+    game.cursor_pos = Some((0, 0));
+    game.do_frame(Duration::from_millis(10));
+    assert_eq!(game.camera_target.center(), Point2::new(0.0, 0.0));
+    game.settings.edge_pan = true;
+    game.do_frame(Duration::from_millis(10));
+    let center = game.camera_target.center();
+    assert!(center.x < 0.0 && center.y > 0.0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_drag_scale_delta() {
+    let mut drag = input::Drag {
+        button: MouseButton::Middle,
+        tile_coords: Point2::new(0.0, 0.0),
+        initial_scale_factor: 16.0,
+
+        cursor_start: (50, 100),
+        cursor_end: (50, 300),
+        past_threshold: true,
+
+        kind: input::DragKind::Scale,
+        flagged_tiles: HashSet::new(),
+    };
+    let mut settings = Settings::default();
+
+    // Dragging down zooms out.
+    assert_eq!(Game::drag_scale_delta(&drag, &settings), -0.5);
+    settings.invert_drag_scale = true;
+    assert_eq!(Game::drag_scale_delta(&drag, &settings), 0.5);
+    settings.drag_scale_pixels_per_2x = 100.0;
+    assert_eq!(Game::drag_scale_delta(&drag, &settings), 2.0);
+    drag.cursor_end = (50, 50);
+    assert_eq!(Game::drag_scale_delta(&drag, &settings), -0.5);
+
+    let mut camera = Camera::default();
+    Game::update_camera_for_drag(&mut camera, &drag, &settings);
+    assert_eq!(
+        camera.scale(),
+        Scale::from_factor(16.0 / 2.0_f64.sqrt()).clamp()
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_alt_click_centers_camera() {
+    let mut game = Game::new();
+    game.camera.set_target_dimensions((100, 100));
+    game.modifiers = ModifiersState::ALT;
+    game.handle_cursor_moved((75, 25));
+    game.handle_mouse_press(MouseButton::Left);
+    game.handle_mouse_release(MouseButton::Left);
+    assert_eq!(game.camera_target().center(), Point2::new(1.5, 1.5));
+    assert!(game.grid.get_chunk(grid::ChunkPos(0, 0)).is_none());
+
+    game.modifiers = ModifiersState::empty();
+    game.handle_mouse_press(MouseButton::Left);
+    game.handle_mouse_release(MouseButton::Left);
+    assert!(matches!(game.grid.get_tile(TilePos(1, 1)), Tile::Number(_)));
+}
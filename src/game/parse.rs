@@ -0,0 +1,34 @@
+use std::fmt;
+
+/// Error returned when parsing text, such as a saved game, fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The version of a saved game is not supported.
+    UnknownVersion(String),
+    /// The seed of a saved game is not a valid number.
+    InvalidSeed(String),
+    /// The camera position at the start of a saved game is missing.
+    MissingCameraPosition,
+    /// A pair of coordinates is not of the form `x,y`.
+    InvalidCoordinates(String),
+    /// The grid of a saved game is malformed.
+    InvalidGrid,
+    /// A character in a text drawing of a grid does not represent any tile.
+    InvalidTile(char),
+    /// A line of a replay is not a valid action.
+    InvalidAction(String),
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownVersion(s) => write!(f, "unknown version {:?}", s),
+            ParseError::InvalidSeed(s) => write!(f, "invalid seed {:?}", s),
+            ParseError::MissingCameraPosition => write!(f, "missing camera position"),
+            ParseError::InvalidCoordinates(s) => write!(f, "invalid coordinates {:?}", s),
+            ParseError::InvalidGrid => write!(f, "invalid grid"),
+            ParseError::InvalidTile(c) => write!(f, "invalid tile {:?}", c),
+            ParseError::InvalidAction(s) => write!(f, "invalid action {:?}", s),
+        }
+    }
+}
+impl std::error::Error for ParseError {}
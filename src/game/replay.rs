@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::{ParseError, TilePos};
+
+/// Player action that modifies the grid.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Reveals a tile, or the tiles adjacent to a number.
+    Reveal(TilePos),
+    /// Toggles the flag on a tile.
+    ToggleFlag(TilePos),
+    /// Undoes the last action.
+    Undo,
+    /// Removes every flag and question mark.
+    ClearFlags,
+}
+
+/// Player action along with the time at which it was performed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimedAction {
+    /// Time since the start of the recording, rounded down to the millisecond.
+    pub time: Duration,
+    /// Action performed.
+    pub action: Action,
+}
+
+/// Recording of a game, which can be replayed on a new grid with the same
+/// seed.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Replay {
+    /// Seed of the grid.
+    pub seed: u64,
+    /// Actions performed by the player, in order.
+    pub actions: Vec<TimedAction>,
+}
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "seed {}", self.seed)?;
+        for TimedAction { time, action } in &self.actions {
+            let time = time.as_millis();
+            match action {
+                Action::Reveal(pos) => writeln!(f, "{} reveal {}", time, pos)?,
+                Action::ToggleFlag(pos) => writeln!(f, "{} flag {}", time, pos)?,
+                Action::Undo => writeln!(f, "{} undo", time)?,
+                Action::ClearFlags => writeln!(f, "{} clear-flags", time)?,
+            }
+        }
+        Ok(())
+    }
+}
+impl FromStr for Replay {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        let seed_line = lines.next().unwrap_or("");
+        let invalid_seed = || ParseError::InvalidSeed(seed_line.to_owned());
+        let seed = seed_line.strip_prefix("seed").ok_or_else(invalid_seed)?;
+        let mut ret = Self {
+            seed: seed.trim().parse().map_err(|_| invalid_seed())?,
+            actions: vec![],
+        };
+
+        for line in lines {
+            let invalid = || ParseError::InvalidAction(line.to_owned());
+            let mut words = line.split_whitespace();
+            let time = words
+                .next()
+                .and_then(|w| w.parse().ok())
+                .ok_or_else(invalid)?;
+            let name = words.next().ok_or_else(invalid)?;
+            let mut pos = || words.next().ok_or_else(invalid)?.parse();
+            let action = match name {
+                "reveal" => Action::Reveal(pos()?),
+                "flag" => Action::ToggleFlag(pos()?),
+                "undo" => Action::Undo,
+                "clear-flags" => Action::ClearFlags,
+                _ => return Err(invalid()),
+            };
+            ret.actions.push(TimedAction {
+                time: Duration::from_millis(time),
+                action,
+            });
+        }
+
+        Ok(ret)
+    }
+}
+
+/// Recording in progress.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    /// Total time played when the recording started.
+    start: Duration,
+    /// Recording so far.
+    replay: Replay,
+}
+impl Recorder {
+    /// Starts a new recording.
+    pub fn new(seed: u64, elapsed: Duration) -> Self {
+        Self {
+            start: elapsed,
+            replay: Replay {
+                seed,
+                actions: vec![],
+            },
+        }
+    }
+
+    /// Records an action.
+    pub fn record(&mut self, action: Action, elapsed: Duration) {
+        let time = elapsed.saturating_sub(self.start);
+        self.replay.actions.push(TimedAction {
+            time: Duration::from_millis(time.as_millis() as u64),
+            action,
+        });
+    }
+
+    /// Finishes the recording.
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+}
+
+/// Replay in progress.
+#[derive(Debug, Clone)]
+pub struct Playback {
+    /// Actions that have not yet been performed.
+    actions: VecDeque<TimedAction>,
+    /// Time since the start of the replay, scaled by `speed`.
+    time: Duration,
+    /// Playback speed multiplier, which may be infinite.
+    speed: f64,
+}
+impl Playback {
+    /// Starts playing back a replay at a speed multiplier, which may be
+    /// `f64::INFINITY` to perform all actions at once.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `speed` is not greater than zero.
+    pub fn new(replay: Replay, speed: f64) -> Self {
+        assert!(
+            speed > 0.0,
+            "Playback speed must be a positive number, not {}",
+            speed,
+        );
+        Self {
+            actions: replay.actions.into(),
+            time: Duration::ZERO,
+            speed,
+        }
+    }
+
+    /// Advances the playback by some amount of real time and returns the
+    /// actions that should be performed.
+    pub fn advance(&mut self, duration: Duration) -> Vec<Action> {
+        if self.speed.is_infinite() {
+            return self.actions.drain(..).map(|a| a.action).collect();
+        }
+        self.time += duration.mul_f64(self.speed);
+        let mut ret = vec![];
+        while let Some(a) = self.actions.front() {
+            if a.time > self.time {
+                break;
+            }
+            ret.push(a.action);
+            self.actions.pop_front();
+        }
+        ret
+    }
+
+    /// Returns `true` if all actions have been performed.
+    pub fn is_done(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_replay_string_round_trip() {
+    let replay = Replay {
+        seed: 1234,
+        actions: vec![
+            TimedAction {
+                time: Duration::from_millis(0),
+                action: Action::Reveal(TilePos(3, -4)),
+            },
+            TimedAction {
+                time: Duration::from_millis(1500),
+                action: Action::ToggleFlag(TilePos(-20, 7)),
+            },
+            TimedAction {
+                time: Duration::from_millis(1600),
+                action: Action::Undo,
+            },
+            TimedAction {
+                time: Duration::from_millis(2000),
+                action: Action::ClearFlags,
+            },
+        ],
+    };
+    assert_eq!(replay.to_string().parse(), Ok(replay));
+    assert_eq!(
+        "seed 1\n0 explode 0,0".parse::<Replay>(),
+        Err(ParseError::InvalidAction("0 explode 0,0".to_owned())),
+    );
+    assert_eq!(
+        "seed 1\n0 reveal".parse::<Replay>(),
+        Err(ParseError::InvalidAction("0 reveal".to_owned())),
+    );
+    assert_eq!(
+        "seed 1\n0 reveal 0;0".parse::<Replay>(),
+        Err(ParseError::InvalidCoordinates("0;0".to_owned())),
+    );
+    assert_eq!(
+        "seed x".parse::<Replay>(),
+        Err(ParseError::InvalidSeed("seed x".to_owned())),
+    );
+    assert_eq!(
+        "".parse::<Replay>(),
+        Err(ParseError::InvalidSeed("".to_owned()))
+    );
+}
@@ -19,7 +19,11 @@ impl Default for Scale {
 }
 impl fmt::Display for Scale {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}:1", self.log2_factor.exp2().round())
+        if self.log2_factor >= 0.0 {
+            write!(f, "{}:1", self.pixels_per_tile().round())
+        } else {
+            write!(f, "1:{}", self.tiles_per_pixel().round())
+        }
     }
 }
 
@@ -48,14 +52,25 @@ impl Scale {
         Self::from_log2_factor(factor.log2())
     }
 
+    /// Returns the smallest scale allowed by `clamp()`; i.e. the furthest the
+    /// player can zoom out.
+    pub fn min() -> Self {
+        Self::from_log2_factor(Self::LOWER_LIMIT)
+    }
+    /// Returns the largest scale allowed by `clamp()`; i.e. the furthest the
+    /// player can zoom in.
+    pub fn max() -> Self {
+        Self::from_log2_factor(Self::UPPER_LIMIT)
+    }
+
     /// Clamps the scale to the lower and upper limits. This is not
     /// automatically enforced by `Scale`; it must be called manually.
     #[must_use = "This method returns a new value instead of mutating its input"]
     pub fn clamp(self) -> Self {
         if self.log2_factor < Self::LOWER_LIMIT {
-            Self::from_log2_factor(Self::LOWER_LIMIT)
+            Self::min()
         } else if self.log2_factor > Self::UPPER_LIMIT {
-            Self::from_log2_factor(Self::UPPER_LIMIT)
+            Self::max()
         } else {
             self
         }
@@ -73,6 +88,21 @@ impl Scale {
     pub fn inv_factor(self) -> f64 {
         1.0 / self.factor()
     }
+    /// Returns the length of pixels per tile. This is the same as `factor()`.
+    pub fn pixels_per_tile(self) -> f64 {
+        self.factor()
+    }
+    /// Returns the length of tiles per pixel. This is the same as
+    /// `inv_factor()`.
+    pub fn tiles_per_pixel(self) -> f64 {
+        self.inv_factor()
+    }
+
+    /// Returns a scale that is some fraction 0.0 <= t <= 1.0 of the way between
+    /// two scales, interpolating the scale factor logarithmically.
+    pub fn lerp(a: Self, b: Self, t: f64) -> Self {
+        Self::from_log2_factor(a.log2_factor + (b.log2_factor - a.log2_factor) * t)
+    }
 
     /// Rounds the scale factor to the nearest power of 2.
     pub fn round(self) -> Self {
@@ -124,3 +154,26 @@ impl Div<Scale> for Scale {
         (self.log2_factor - other.log2_factor).exp2()
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_scale_clamp_bounds() {
+    assert!(Scale::min() < Scale::max());
+    for i in -40..=40 {
+        let clamped = Scale::from_log2_factor(i as f64 * 0.25).clamp();
+        assert!(Scale::min() <= clamped && clamped <= Scale::max());
+    }
+    assert_eq!(Scale::from_factor(1.0).clamp(), Scale::min());
+    assert_eq!(Scale::from_factor(1000.0).clamp(), Scale::max());
+    assert_eq!(Scale::from_factor(16.0).clamp(), Scale::from_factor(16.0));
+}
+
+#[cfg(test)]
+#[test]
+fn test_scale_display() {
+    assert_eq!(Scale::from_factor(16.0).to_string(), "16:1");
+    assert_eq!(Scale::from_factor(1.0).to_string(), "1:1");
+    assert_eq!(Scale::from_factor(0.5).to_string(), "1:2");
+    assert_eq!(Scale::from_factor(0.5).tiles_per_pixel(), 2.0);
+    assert_eq!(Scale::from_factor(16.0).pixels_per_tile(), 16.0);
+}
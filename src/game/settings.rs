@@ -0,0 +1,238 @@
+use cgmath::Vector2;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Behavior of the first tile revealed in a game.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum FirstReveal {
+    /// The first tile revealed is never a mine, but may be any number.
+    #[default]
+    SafeTile,
+    /// The first tile revealed is always a zero, revealing a large opening as
+    /// in classic Minesweeper. This always reveals at least the 3x3 square
+    /// around the first tile, even where mines are dense.
+    Opening,
+}
+
+/// Colors used to draw numbers, flags, and mines.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Palette {
+    /// Original colors of the sprites.
+    #[default]
+    Classic,
+    /// Colors distinguishable with deuteranopia.
+    Deuteranopia,
+    /// Colors distinguishable with protanopia.
+    Protanopia,
+    /// Colors distinguishable with tritanopia.
+    Tritanopia,
+}
+impl Palette {
+    /// List of all palettes, in the order they are cycled through.
+    pub const ALL: &'static [Self] = &[
+        Palette::Classic,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+    ];
+
+    /// Returns the palette after this one, wrapping around to the first.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// User-configurable settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Whether to shade alternating covered tiles in a checkerboard pattern.
+    pub checkerboard: bool,
+    /// Whether to require a second click to reveal a tile that is not known to
+    /// be safe based on adjacent flags.
+    pub confirm_risky_reveals: bool,
+    /// Whether right clicking a flag turns it into a question mark instead of
+    /// removing it.
+    pub question_marks_enabled: bool,
+    /// Whether question marks count as flags when uncovering the squares
+    /// around a number.
+    pub question_marks_as_flags: bool,
+    /// Whether clicking a question-marked square uncovers it. If not, question
+    /// marks protect squares from being uncovered like flags do.
+    pub reveal_question_marked: bool,
+    /// Distance from the cursor within which Ctrl+click uncovers squares
+    /// known to be safe.
+    pub reveal_radius: u32,
+    /// Whether this is a practice game, in which the move that lost the game
+    /// can be undone to keep playing.
+    pub practice_mode: bool,
+    /// Whether to draw a marker on the square under the mouse cursor, which
+    /// is easier to follow than the mouse cursor on touch screens or when
+    /// presenting.
+    pub show_cursor_sprite: bool,
+    /// Behavior of the first tile revealed in a game.
+    pub first_reveal: FirstReveal,
+    /// Whether to place the mines in a finite game again when the first tile
+    /// is revealed, until the rest can be solved without guessing. This gives
+    /// up after `MAX_NO_GUESS_ATTEMPTS` tries.
+    pub avoid_guessing: bool,
+    /// Whether to shade covered tiles in chunks where mines have not been
+    /// placed yet, showing the frontier of exploration.
+    pub show_explored: bool,
+    /// Whether to color each chunk where mines have been placed by the
+    /// fraction of its tiles that are mines, for debugging mine placement.
+    pub show_density: bool,
+    /// Whether to smooth tiles while the camera is moving.
+    pub smooth_motion: bool,
+    /// Whether to use smaller, pre-scaled copies of the spritesheet when zoomed
+    /// out. Without them, tiles look sharper but may shimmer.
+    pub mipmaps: bool,
+    /// Whether to offset the grid by half a pixel when the window has an odd
+    /// width or height, so that tiles stay crisp. Disabling this avoids a
+    /// slight shimmer on some GPUs when resizing the window.
+    pub half_pixel_offset: bool,
+    /// Whether to draw tiles at the nearest power-of-two scale, aligned to
+    /// whole pixels, such as for screenshots.
+    pub pixel_perfect: bool,
+    /// Offset in tiles at which the grid is drawn, such as half a tile for
+    /// variants drawn on a staggered grid. This does not change which tile is
+    /// under the cursor.
+    pub render_offset: Vector2<f64>,
+    /// Colors used to draw numbers, flags, and mines.
+    pub palette: Palette,
+    /// Whether dragging down with the middle mouse button zooms in instead of
+    /// out.
+    pub invert_drag_scale: bool,
+    /// Number of pixels to drag with the middle mouse button to zoom by a
+    /// factor of 2. This must be positive.
+    pub drag_scale_pixels_per_2x: f64,
+    /// Whether to pan when the cursor is near the edge of the window.
+    pub edge_pan: bool,
+    /// Distance in pixels from the edge of the window within which the cursor
+    /// causes panning.
+    pub edge_pan_margin: u32,
+    /// Speed in pixels per second to pan when the cursor is at the very edge
+    /// of the window.
+    pub edge_pan_speed: f64,
+    /// Whether zooming with the scroll wheel keeps the tile under the cursor
+    /// in place, instead of zooming around the center.
+    pub wheel_zoom_around_cursor: bool,
+    /// Whether zooming with the keyboard keeps the tile under the cursor in
+    /// place, like the scroll wheel, instead of zooming around the center.
+    pub keyboard_zoom_around_cursor: bool,
+    /// Maximum number of chunks to generate in one frame while solving
+    /// automatically. At least one tile is always revealed each frame, even if
+    /// it generates more chunks than this.
+    pub max_chunks_generated_per_frame: usize,
+    /// How far the view shakes, in pixels, when the player hits a mine. Zero
+    /// disables shaking.
+    pub shake_intensity: f64,
+    /// Minimum time between frames. While nothing is changing on screen, no
+    /// frames are drawn at all until the next input.
+    pub min_frame_duration: Duration,
+    /// File to append statistics to at the end of each game, one line of JSON
+    /// per game, or `None` to not save statistics. See
+    /// `Game::default_stats_file_path()`.
+    pub stats_file: Option<PathBuf>,
+    /// File to save the game to, or `None` to never save it. This is set by
+    /// `Game::load_from_file()`.
+    pub save_file: Option<PathBuf>,
+    /// File to save the fastest win for each preset to, or `None` to not save
+    /// best times. See `Game::default_best_times_file_path()`.
+    pub best_times_file: Option<PathBuf>,
+    /// Factor to multiply the color of each tile by.
+    brightness: f32,
+}
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            checkerboard: false,
+            confirm_risky_reveals: false,
+            question_marks_enabled: false,
+            question_marks_as_flags: false,
+            reveal_question_marked: true,
+            reveal_radius: 2,
+            practice_mode: false,
+            show_cursor_sprite: false,
+            first_reveal: FirstReveal::default(),
+            avoid_guessing: false,
+            show_explored: false,
+            show_density: false,
+            smooth_motion: false,
+            mipmaps: true,
+            half_pixel_offset: true,
+            pixel_perfect: false,
+            render_offset: Vector2::new(0.0, 0.0),
+            palette: Palette::default(),
+            invert_drag_scale: false,
+            drag_scale_pixels_per_2x: super::camera::PIXELS_PER_2X_SCALE,
+            edge_pan: false,
+            edge_pan_margin: 16,
+            edge_pan_speed: 500.0,
+            wheel_zoom_around_cursor: true,
+            keyboard_zoom_around_cursor: false,
+            max_chunks_generated_per_frame: 16,
+            shake_intensity: 6.0,
+            min_frame_duration: Duration::from_secs_f64(1.0 / 60.0),
+            stats_file: None,
+            save_file: None,
+            best_times_file: None,
+            brightness: 1.0,
+        }
+    }
+}
+impl Settings {
+    /// The lowest allowed brightness, which keeps tiles visible.
+    pub const MIN_BRIGHTNESS: f32 = 0.25;
+    /// The highest allowed brightness.
+    pub const MAX_BRIGHTNESS: f32 = 2.0;
+    /// Amount that the brightness changes by each time a key is pressed.
+    pub const BRIGHTNESS_STEP: f32 = 0.25;
+
+    /// Returns the factor to multiply the color of each tile by.
+    pub fn brightness(&self) -> f32 {
+        self.brightness
+    }
+    /// Sets the factor to multiply the color of each tile by, clamped between
+    /// `MIN_BRIGHTNESS` and `MAX_BRIGHTNESS`. `NaN` resets it to the default.
+    pub fn set_brightness(&mut self, brightness: f32) {
+        self.brightness = if brightness.is_nan() {
+            Self::default().brightness
+        } else {
+            brightness.clamp(Self::MIN_BRIGHTNESS, Self::MAX_BRIGHTNESS)
+        };
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_brightness_clamp() {
+    let mut settings = Settings::default();
+    assert_eq!(settings.brightness(), 1.0);
+    settings.set_brightness(1.5);
+    assert_eq!(settings.brightness(), 1.5);
+    settings.set_brightness(0.0);
+    assert_eq!(settings.brightness(), Settings::MIN_BRIGHTNESS);
+    settings.set_brightness(-3.0);
+    assert_eq!(settings.brightness(), Settings::MIN_BRIGHTNESS);
+    settings.set_brightness(f32::INFINITY);
+    assert_eq!(settings.brightness(), Settings::MAX_BRIGHTNESS);
+    settings.set_brightness(f32::NAN);
+    assert_eq!(settings.brightness(), 1.0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_palette_next() {
+    let mut palette = Palette::default();
+    let mut seen = vec![];
+    for _ in 0..Palette::ALL.len() {
+        seen.push(palette);
+        palette = palette.next();
+    }
+    assert_eq!(seen, Palette::ALL);
+    // It wraps around.
+    assert_eq!(palette, Palette::default());
+    assert_eq!(Palette::Tritanopia.next(), Palette::Classic);
+}
@@ -0,0 +1,388 @@
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+
+use super::{tiles_in_rect, FlagState, Grid, HiddenState, RevealOptions, Tile, TilePos};
+
+/// Covered tiles whose contents can be determined from revealed tiles.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Deductions {
+    /// Covered tiles that are definitely safe.
+    pub safe: Vec<TilePos>,
+    /// Covered unflagged tiles that are definitely mines.
+    pub mines: Vec<TilePos>,
+}
+impl Deductions {
+    /// Returns `true` if nothing could be deduced.
+    pub fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.mines.is_empty()
+    }
+}
+
+/// Constraint on the covered tiles around a number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Constraint {
+    /// Covered unflagged tiles adjacent to the number.
+    tiles: Vec<TilePos>,
+    /// Total weight of the mines among `tiles`.
+    mines: usize,
+    /// Total weight of `tiles`, which is the value of `mines` if they are all
+    /// mines.
+    capacity: usize,
+}
+impl Constraint {
+    /// Returns the constraint imposed by a tile, or `None` if it is not a
+    /// number or it has more flags than mines around it.
+    fn at(grid: &Grid, pos: TilePos) -> Option<Self> {
+        // Walls look like numbers, but say nothing about their neighbors.
+        if !grid.in_arena(pos) {
+            return None;
+        }
+        let n = match grid.get_tile(pos) {
+            Tile::Number(n) => n as usize,
+            _ => return None,
+        };
+        let assumed_mines = grid.count_assumed_mines(pos, RevealOptions::default());
+        let mines = n.checked_sub(assumed_mines as usize)?;
+        let tiles = grid
+            .neighbor_summary(pos)
+            .covered_tiles(pos)
+            .collect::<Vec<_>>();
+        let capacity = tiles.iter().map(|&t| grid.mine_weight(t) as usize).sum();
+        Some(Self {
+            tiles,
+            mines,
+            capacity,
+        })
+    }
+}
+
+/// Deduces which covered tiles are safe and which are mines, considering only
+/// the numbers in a rectangle, including `min` and `max`. Deduced tiles may be
+/// just outside the rectangle.
+///
+/// This uses `deduce_single()`, and then `deduce_subsets()` only if that finds
+/// nothing, since comparing numbers is slower.
+///
+/// Only information visible to the player is used, and flags are assumed to be
+/// correct.
+pub fn deduce(grid: &Grid, min: TilePos, max: TilePos) -> Deductions {
+    let ret = deduce_single(grid, min, max);
+    if ret.is_empty() {
+        deduce_subsets(grid, min, max)
+    } else {
+        ret
+    }
+}
+
+/// Returns `true` if no number in a rectangle, including `min` and `max`, has
+/// more flags around it than its value. A wrong flag isn't always visible, but
+/// when one is, deductions from the flags nearby can't be trusted.
+pub fn flags_consistent(grid: &Grid, min: TilePos, max: TilePos) -> bool {
+    tiles_in_rect(min, max).all(|pos| match grid.get_tile(pos) {
+        Tile::Number(n) if grid.in_arena(pos) => {
+            grid.count_assumed_mines(pos, RevealOptions::default()) <= n
+        }
+        _ => true,
+    })
+}
+
+/// Deduces which covered tiles are safe and which are mines like `deduce()`,
+/// but considering each number on its own: if all of its mines are flagged,
+/// the rest of its covered neighbors are safe, and if it has exactly as many
+/// covered neighbors as unflagged mines, they are all mines.
+pub fn deduce_single(grid: &Grid, min: TilePos, max: TilePos) -> Deductions {
+    let mut ret = Deductions::default();
+    let mut seen = HashSet::new();
+    for pos in tiles_in_rect(min, max) {
+        let constraint = match Constraint::at(grid, pos) {
+            Some(c) if !c.tiles.is_empty() => c,
+            _ => continue,
+        };
+        let list = if constraint.mines == 0 {
+            &mut ret.safe
+        } else if constraint.mines == constraint.capacity {
+            &mut ret.mines
+        } else {
+            continue;
+        };
+        for tile in constraint.tiles {
+            if seen.insert(tile) {
+                list.push(tile);
+            }
+        }
+    }
+    ret
+}
+
+/// Deduces which covered tiles are safe and which are mines like `deduce()`,
+/// but by comparing pairs of numbers: if the covered neighbors of one number
+/// are a subset of another's, then the remaining covered neighbors of the
+/// second number have as many mines as the difference between their counts.
+/// This solves patterns such as 1-2-1 along an edge.
+///
+/// Tiles that `deduce_single()` would find are not necessarily found.
+pub fn deduce_subsets(grid: &Grid, min: TilePos, max: TilePos) -> Deductions {
+    let constraints = tiles_in_rect(min, max)
+        .filter_map(|pos| Constraint::at(grid, pos))
+        .filter(|c| !c.tiles.is_empty())
+        .collect_vec();
+    let mut constraints_by_tile: HashMap<TilePos, Vec<usize>> = HashMap::new();
+    for (i, constraint) in constraints.iter().enumerate() {
+        for &tile in &constraint.tiles {
+            constraints_by_tile.entry(tile).or_default().push(i);
+        }
+    }
+
+    let mut ret = Deductions::default();
+    let mut seen = HashSet::new();
+    for small in &constraints {
+        // Any superset contains the first tile of the subset.
+        for &i in &constraints_by_tile[&small.tiles[0]] {
+            let big = &constraints[i];
+            if big.tiles.len() <= small.tiles.len()
+                || !small.tiles.iter().all(|t| big.tiles.contains(t))
+            {
+                continue;
+            }
+            // Flags are assumed to be correct, but skip contradictions anyway.
+            let mines = match big.mines.checked_sub(small.mines) {
+                Some(m) => m,
+                None => continue,
+            };
+            let list = if mines == 0 {
+                &mut ret.safe
+            } else if mines == big.capacity - small.capacity {
+                &mut ret.mines
+            } else {
+                continue;
+            };
+            for &tile in &big.tiles {
+                if !small.tiles.contains(&tile) && seen.insert(tile) {
+                    list.push(tile);
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// How hard a board is to solve without guessing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    /// Solvable using only `deduce_single()`.
+    Trivial,
+    /// Solvable, but only by also using `deduce_subsets()`.
+    Intermediate,
+    /// Not solvable by the rules the solver knows, so the player would have to
+    /// guess (or know more advanced tricks).
+    RequiresGuess,
+}
+
+/// Rates how hard it is to reveal every safe tile in the rectangle `arena`,
+/// starting from the tiles that are already revealed.
+///
+/// This solves a copy of the grid using the hidden mine locations, so mines
+/// must already be placed in `arena`. Flags are assumed to be correct.
+pub fn rate_difficulty(grid: &Grid, arena: (TilePos, TilePos)) -> Difficulty {
+    let (min, max) = arena;
+    let in_rect =
+        |TilePos(x, y): &TilePos| min.0 <= *x && *x <= max.0 && min.1 <= *y && *y <= max.1;
+    let restrict = |mut deductions: Deductions| {
+        deductions.safe.retain(in_rect);
+        deductions.mines.retain(in_rect);
+        deductions
+    };
+
+    let mut grid = grid.clone();
+    let mut ret = Difficulty::Trivial;
+    loop {
+        let solved = tiles_in_rect(min, max).all(|pos| match grid.get_tile(pos) {
+            Tile::Covered(_, h) => h == HiddenState::Mine,
+            _ => true,
+        });
+        if solved {
+            return ret;
+        }
+
+        let mut deductions = restrict(deduce_single(&grid, min, max));
+        if deductions.is_empty() {
+            deductions = restrict(deduce_subsets(&grid, min, max));
+            ret = Difficulty::Intermediate;
+        }
+        if deductions.is_empty() {
+            return Difficulty::RequiresGuess;
+        }
+
+        for pos in deductions.mines {
+            if let Tile::Covered(_, h) = grid.get_tile(pos) {
+                grid.set_tile(pos, Tile::Covered(FlagState::Flag, h));
+            }
+        }
+        for pos in deductions.safe {
+            // A wrong flag can lead to a wrong deduction.
+            if grid.reveal(pos, RevealOptions::default()).hit_mine {
+                return Difficulty::RequiresGuess;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_deduce() {
+    let mut grid = Grid::with_seed(0);
+    let covered = Tile::Covered(FlagState::None, HiddenState::Unknown);
+    let flag = Tile::Covered(FlagState::Flag, HiddenState::Unknown);
+    // Row of numbers, with covered tiles above it:
+    //
+    //     # # # # # #
+    //     2 2 2 F 1 #
+    //     0 0 0 0 0 0
+    for x in 0..6 {
+        grid.set_tile(TilePos(x, 0), Tile::Number(0));
+        grid.set_tile(TilePos(x, 2), covered);
+    }
+    for y in 0..=2 {
+        grid.set_tile(TilePos(-1, y), Tile::Number(0));
+    }
+    grid.set_tile(TilePos(0, 1), Tile::Number(2));
+    grid.set_tile(TilePos(1, 1), Tile::Number(2));
+    grid.set_tile(TilePos(2, 1), Tile::Number(2));
+    grid.set_tile(TilePos(3, 1), flag);
+    grid.set_tile(TilePos(4, 1), Tile::Number(1));
+    grid.set_tile(TilePos(5, 1), covered);
+
+    // (1, 1) and (2, 1) each have more covered neighbors than unflagged mines,
+    // so nothing can be deduced from them on their own.
+    let deductions = deduce(&grid, TilePos(0, 1), TilePos(4, 1));
+    assert_eq!(
+        deductions,
+        Deductions {
+            safe: vec![TilePos(3, 2), TilePos(4, 2), TilePos(5, 1), TilePos(5, 2)],
+            mines: vec![TilePos(0, 2), TilePos(1, 2)],
+        },
+    );
+    // Numbers outside the rectangle are ignored.
+    assert!(deduce(&grid, TilePos(2, 1), TilePos(3, 1)).is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn test_deduce_subsets() {
+    /// Returns a grid with a row of numbers at y=1 starting at x=0, with
+    /// covered tiles above them and revealed tiles everywhere else nearby.
+    fn numbers_under_covered_row(numbers: &[u8]) -> Grid {
+        let mut grid = Grid::with_seed(0);
+        let width = numbers.len() as i32;
+        for x in -1..=width {
+            grid.set_tile(TilePos(x, 0), Tile::Number(0));
+        }
+        for y in 1..=2 {
+            grid.set_tile(TilePos(-1, y), Tile::Number(0));
+            grid.set_tile(TilePos(width, y), Tile::Number(0));
+        }
+        for (x, &n) in numbers.iter().enumerate() {
+            grid.set_tile(TilePos(x as i32, 1), Tile::Number(n));
+            grid.set_tile(TilePos(x as i32, 2), Tile::default());
+        }
+        grid
+    }
+    fn flag(grid: &mut Grid, mines: &[TilePos]) {
+        for &pos in mines {
+            grid.set_tile(pos, Tile::Covered(FlagState::Flag, HiddenState::Unknown));
+        }
+    }
+
+    // 1-2-1: the mines are above the 1s.
+    //
+    //     # # #
+    //     1 2 1
+    let mut grid = numbers_under_covered_row(&[1, 2, 1]);
+    let (min, max) = (TilePos(0, 1), TilePos(2, 1));
+    assert!(deduce_single(&grid, min, max).is_empty());
+    let deductions = deduce(&grid, min, max);
+    assert_eq!(deductions, deduce_subsets(&grid, min, max));
+    assert_eq!(
+        deductions,
+        Deductions {
+            safe: vec![],
+            mines: vec![TilePos(2, 2), TilePos(0, 2)],
+        },
+    );
+    // Then the tile between them is safe.
+    flag(&mut grid, &deductions.mines);
+    assert_eq!(
+        deduce(&grid, min, max),
+        Deductions {
+            safe: vec![TilePos(1, 2)],
+            mines: vec![],
+        },
+    );
+
+    // 1-2-2-1: the mines are above the 2s.
+    //
+    //     # # # #
+    //     1 2 2 1
+    let mut grid = numbers_under_covered_row(&[1, 2, 2, 1]);
+    let (min, max) = (TilePos(0, 1), TilePos(3, 1));
+    assert!(deduce_single(&grid, min, max).is_empty());
+    let deductions = deduce(&grid, min, max);
+    assert_eq!(
+        deductions,
+        Deductions {
+            safe: vec![],
+            mines: vec![TilePos(2, 2), TilePos(1, 2)],
+        },
+    );
+    // Then the tiles above the 1s are safe.
+    flag(&mut grid, &deductions.mines);
+    assert_eq!(
+        deduce(&grid, min, max),
+        Deductions {
+            safe: vec![TilePos(0, 2), TilePos(3, 2)],
+            mines: vec![],
+        },
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_rate_difficulty() {
+    /// Returns an arena drawn with `*` for mines and `.` for safe tiles, with
+    /// the bottom left tile at (0, 0), after revealing `start`.
+    fn arena_grid(rows: &[&str], start: TilePos) -> (Grid, (TilePos, TilePos)) {
+        let height = rows.len() as i32;
+        let width = rows[0].len() as i32;
+        let arena = (TilePos(0, 0), TilePos(width - 1, height - 1));
+        let mut grid = Grid::with_seed(0);
+        grid.set_arena(Some(arena));
+        grid.for_each_tile_in_rect(arena.0, arena.1, |TilePos(x, y), tile| {
+            let h = match rows[(height - 1 - y) as usize].as_bytes()[x as usize] {
+                b'*' => HiddenState::Mine,
+                _ => HiddenState::Safe,
+            };
+            *tile = Tile::Covered(FlagState::None, h);
+        });
+        grid.reveal(start, RevealOptions::default());
+        (grid, arena)
+    }
+
+    // The two mines on the right are next to a 2 with only two covered
+    // neighbors, and then the 1 at the bottom has only one covered neighbor.
+    let (grid, arena) = arena_grid(&["..*", "..*", "..."], TilePos(0, 0));
+    assert_eq!(rate_difficulty(&grid, arena), Difficulty::Trivial);
+
+    // 1-2-1 along the right edge: the 1s have fewer covered neighbors than the
+    // 2, so the mines must be on either end.
+    let (grid, arena) = arena_grid(&["..*", "...", "..*"], TilePos(0, 0));
+    assert_eq!(rate_difficulty(&grid, arena), Difficulty::Intermediate);
+
+    // The wall of mines hides the right side of the arena.
+    let (grid, arena) = arena_grid(&["..*..", "..*.."], TilePos(0, 0));
+    assert_eq!(rate_difficulty(&grid, arena), Difficulty::RequiresGuess);
+
+    // Nothing can be deduced before anything is revealed.
+    let mut grid = Grid::with_seed(0);
+    grid.set_arena(Some(arena));
+    assert_eq!(rate_difficulty(&grid, arena), Difficulty::RequiresGuess);
+}
@@ -0,0 +1,167 @@
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use super::{GameState, Preset};
+
+/// Summary of a game that has ended, which can be saved to track progress
+/// over many games.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameStats {
+    /// Seed used to place mines.
+    pub seed: u64,
+    /// Probability that each tile is a mine.
+    pub mine_density: f64,
+    /// Total time spent playing.
+    pub elapsed: Duration,
+    /// Number of tiles revealed.
+    pub tiles_revealed: usize,
+    /// Number of flags placed.
+    pub flags_placed: usize,
+    /// How the game ended.
+    pub outcome: GameState,
+}
+impl GameStats {
+    /// Returns the statistics as a JSON object.
+    pub fn to_json(&self) -> serde_json::Value {
+        let outcome = match self.outcome {
+            GameState::Playing => "playing",
+            GameState::Lost => "lost",
+            GameState::Won => "won",
+        };
+        json!({
+            "seed": self.seed,
+            "mine_density": self.mine_density,
+            "elapsed_ms": self.elapsed.as_millis() as u64,
+            "tiles_revealed": self.tiles_revealed,
+            "flags_placed": self.flags_placed,
+            "outcome": outcome,
+        })
+    }
+
+    /// Appends the statistics to a file as a single line of JSON, creating the
+    /// file if it does not exist.
+    pub fn append_to_file(&self, path: &Path) -> io::Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", self.to_json())
+    }
+}
+
+/// Fastest win for each preset, which can be saved to keep track of high
+/// scores.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BestTimes {
+    times: HashMap<Preset, Duration>,
+}
+impl BestTimes {
+    /// Returns the fastest win for a preset, or `None` if it has never been
+    /// won.
+    pub fn get(&self, preset: Preset) -> Option<Duration> {
+        self.times.get(&preset).copied()
+    }
+    /// Records a win, and returns `true` if it is the fastest for its preset.
+    pub fn record(&mut self, preset: Preset, elapsed: Duration) -> bool {
+        match self.get(preset) {
+            Some(best) if best <= elapsed => false,
+            _ => {
+                self.times.insert(preset, elapsed);
+                true
+            }
+        }
+    }
+
+    /// Returns the best times as a JSON object, with the time in milliseconds
+    /// for each preset that has been won.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut ret = serde_json::Map::new();
+        for &preset in Preset::ALL {
+            if let Some(time) = self.get(preset) {
+                ret.insert(preset.name().to_owned(), json!(time.as_millis() as u64));
+            }
+        }
+        serde_json::Value::Object(ret)
+    }
+    /// Returns the best times from a JSON object in the format returned by
+    /// `to_json()`. Unknown presets and invalid times are ignored.
+    pub fn from_json(value: &serde_json::Value) -> Self {
+        let mut ret = Self::default();
+        for &preset in Preset::ALL {
+            if let Some(ms) = value.get(preset.name()).and_then(|v| v.as_u64()) {
+                ret.times.insert(preset, Duration::from_millis(ms));
+            }
+        }
+        ret
+    }
+
+    /// Loads best times from a file, or returns no best times if the file does
+    /// not exist.
+    pub fn load_from_file(path: &Path) -> io::Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        let value = serde_json::from_str(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Self::from_json(&value))
+    }
+    /// Saves the best times to a file, replacing its contents.
+    pub fn save_to_file(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, self.to_json().to_string())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_game_stats_json() {
+    let stats = GameStats {
+        seed: 42,
+        mine_density: 0.2,
+        elapsed: Duration::from_micros(83_456_789),
+        tiles_revealed: 1234,
+        flags_placed: 56,
+        outcome: GameState::Won,
+    };
+    assert_eq!(
+        stats.to_json(),
+        json!({
+            "seed": 42,
+            "mine_density": 0.2,
+            "elapsed_ms": 83456,
+            "tiles_revealed": 1234,
+            "flags_placed": 56,
+            "outcome": "won",
+        }),
+    );
+    assert_eq!(
+        stats.to_json().to_string(),
+        r#"{"elapsed_ms":83456,"flags_placed":56,"mine_density":0.2,"outcome":"won","seed":42,"tiles_revealed":1234}"#,
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_best_times_json() {
+    let mut best_times = BestTimes::default();
+    assert!(best_times.record(Preset::Beginner, Duration::from_micros(8_456_789)));
+    assert!(best_times.record(Preset::Expert, Duration::from_secs(300)));
+    let value = best_times.to_json();
+    assert_eq!(value, json!({"beginner": 8456, "expert": 300000}));
+
+    let loaded = BestTimes::from_json(&value);
+    assert_eq!(
+        loaded.get(Preset::Beginner),
+        Some(Duration::from_millis(8456))
+    );
+    assert_eq!(loaded.get(Preset::Intermediate), None);
+    assert_eq!(loaded.get(Preset::Expert), Some(Duration::from_secs(300)));
+    assert_eq!(
+        BestTimes::from_json(&json!({"beginner": "fast", "custom": 1})),
+        BestTimes::default(),
+    );
+}
@@ -1,4 +1,22 @@
+use std::fmt;
+
 /// Tile in the Minesweeper grid, packed into a single byte.
+///
+/// Common tiles are packed into printable ASCII characters, so that save files
+/// are somewhat human-readable:
+///
+/// | Byte            | Tile                                                  |
+/// |-----------------|-------------------------------------------------------|
+/// | `' '`           | `Tile::Number(0)`                                     |
+/// | `'!'`           | `Tile::Mine`                                          |
+/// | `'1'..='9'`     | `Tile::Number(1..=9)`                                 |
+/// | `'A'..='_'`     | `Tile::Number(10..=40)`                               |
+/// | `0x60..=0x7F`   | `Tile::Covered`, with `FlagState` in bits 2-3 and `HiddenState` in bits 0-1 |
+/// | `0x80..=0xFF`   | `Tile::Number(41..=168)`                              |
+///
+/// Numbers above 40 can only occur with unusually large neighbor counts, so
+/// they use the remaining non-ASCII bytes. Every number up to
+/// `Tile::MAX_NUMBER` fits in a single byte.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub(super) struct PackedTile(pub(super) u8);
 impl Default for PackedTile {
@@ -8,20 +26,21 @@ impl Default for PackedTile {
 }
 impl PackedTile {
     /// Unpacks the `Tile` from a single byte.
+    ///
+    /// Bytes that do not correspond to any tile (e.g., from a corrupted save
+    /// file) are unpacked as the default tile.
     pub(super) fn unpack(self) -> Tile {
-        if self.0 == '!' as u8 {
-            Tile::Mine
-        } else if self.0 == ' ' as u8 {
-            Tile::Number(0)
-        } else if self.0 <= '9' as u8 {
-            Tile::Number(self.0 - '0' as u8)
-        } else if self.0 < 0x60 {
-            Tile::Number(self.0 - 'A' as u8 + 10)
-        } else {
-            Tile::Covered(
+        match self.0 {
+            b'!' => Tile::Mine,
+            b' ' => Tile::Number(0),
+            b'0'..=b'9' => Tile::Number(self.0 - b'0'),
+            b'A'..=0x5F => Tile::Number(self.0 - b'A' + 10),
+            0x60..=0x7F => Tile::Covered(
                 FlagState::from((self.0 >> 2) & 0b11),
                 HiddenState::from(self.0 & 0b11),
-            )
+            ),
+            0x80..=0xFF => Tile::Number(self.0 - 0x80 + Tile::MAX_ASCII_NUMBER + 1),
+            _ => Tile::default(),
         }
     }
 }
@@ -41,14 +60,75 @@ impl Default for Tile {
         Tile::Covered(FlagState::default(), HiddenState::default())
     }
 }
+impl fmt::Display for Tile {
+    /// Formats the tile as a single character, which is the same as the packed
+    /// byte for revealed tiles up to 9:
+    ///
+    /// | Character   | Tile                                    |
+    /// |-------------|-----------------------------------------|
+    /// | `'#'`       | `Tile::Covered(FlagState::None, _)`     |
+    /// | `'F'`       | `Tile::Covered(FlagState::Flag, _)`     |
+    /// | `'?'`       | `Tile::Covered(FlagState::Question, _)` |
+    /// | `' '`       | `Tile::Number(0)`                       |
+    /// | `'1'..='9'` | `Tile::Number(1..=9)`                   |
+    /// | `'!'`       | `Tile::Mine`                            |
+    ///
+    /// Numbers above 9 cannot occur in a normal game, so they are written as
+    /// the non-ASCII characters starting at `'\u{10A}'` to keep them distinct
+    /// from other tiles.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match *self {
+            Tile::Covered(FlagState::None, _) => '#',
+            Tile::Covered(FlagState::Flag, _) => 'F',
+            Tile::Covered(FlagState::Question, _) => '?',
+            Tile::Number(n) if n > 9 => {
+                std::char::from_u32(Self::LARGE_NUMBER_CHAR_OFFSET + n as u32).unwrap()
+            }
+            Tile::Number(_) | Tile::Mine => self.pack().0 as char,
+        };
+        write!(f, "{}", c)
+    }
+}
 impl Tile {
+    /// Offset added to numbers above 9 to get the character they are written
+    /// as by `Display`.
+    const LARGE_NUMBER_CHAR_OFFSET: u32 = 0x100;
+
+    /// Returns the tile formatted as a character by `Display`, or `None` if
+    /// there is no such tile. Covered tiles have an unknown hidden state.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '#' => Some(Tile::Covered(FlagState::None, HiddenState::Unknown)),
+            'F' => Some(Tile::Covered(FlagState::Flag, HiddenState::Unknown)),
+            '?' => Some(Tile::Covered(FlagState::Question, HiddenState::Unknown)),
+            ' ' | '1'..='9' | '!' => Some(PackedTile(c as u8).unpack()),
+            _ => {
+                let n = (c as u32).checked_sub(Self::LARGE_NUMBER_CHAR_OFFSET)?;
+                if (10..=Self::MAX_NUMBER as u32).contains(&n) {
+                    Some(Tile::Number(n as u8))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Largest number that can be packed into a printable ASCII character.
+    const MAX_ASCII_NUMBER: u8 = 40;
+    /// Largest number that can be stored in a tile. Larger numbers are clamped
+    /// to this value.
+    pub const MAX_NUMBER: u8 = Self::MAX_ASCII_NUMBER + 0x80;
+
     /// Packs the tile into a single byte.
     pub(super) fn pack(self) -> PackedTile {
         match self {
             Tile::Covered(f, h) => PackedTile(0x60 | (f as u8) << 2 | h as u8),
             Tile::Number(0) => PackedTile(' ' as u8),
             Tile::Number(n) if n < 10 => PackedTile(n + '0' as u8),
-            Tile::Number(n) => PackedTile(n - 10 + 'A' as u8),
+            Tile::Number(n) if n <= Self::MAX_ASCII_NUMBER => PackedTile(n - 10 + 'A' as u8),
+            Tile::Number(n) => {
+                PackedTile(n.min(Self::MAX_NUMBER) - (Self::MAX_ASCII_NUMBER + 1) + 0x80)
+            }
             Tile::Mine => PackedTile('!' as u8),
         }
     }
@@ -69,6 +149,22 @@ impl Tile {
         }
     }
 
+    /// Cycles the annotation on the tile from nothing to a flag, then to a
+    /// question mark if `include_question` is `true`, then back to nothing.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn cycle_flag(self, include_question: bool) -> Tile {
+        match self {
+            Tile::Covered(FlagState::Flag, h) if include_question => {
+                Tile::Covered(FlagState::Question, h)
+            }
+            _ => self.toggle_flag(),
+        }
+    }
+
+    /// Returns `true` if the tile has been revealed.
+    pub fn is_revealed(self) -> bool {
+        !matches!(self, Tile::Covered(_, _))
+    }
     /// Returns `true` if the tile is a mine or `false` if it might not be.
     pub fn is_mine(self) -> bool {
         match self {
@@ -77,6 +173,14 @@ impl Tile {
             _ => false,
         }
     }
+    /// Returns `true` if the tile is covered and flagged, not including
+    /// question marks.
+    pub fn is_flagged(self) -> bool {
+        match self {
+            Tile::Covered(FlagState::Flag, _) => true,
+            _ => false,
+        }
+    }
     /// Returns `true` if the tile is a flag or a revealed mine.
     pub fn is_assumed_mine(self) -> bool {
         match self {
@@ -104,12 +208,13 @@ impl Default for FlagState {
     }
 }
 impl From<u8> for FlagState {
+    /// Converts the lowest two bits of a byte to a `FlagState`, using the
+    /// default for invalid values.
     fn from(x: u8) -> Self {
         match x & 0b11 {
-            0 => FlagState::None,
             1 => FlagState::Flag,
             2 => FlagState::Question,
-            _ => panic!("Invalid FlagState"),
+            _ => FlagState::default(),
         }
     }
 }
@@ -131,12 +236,13 @@ impl Default for HiddenState {
     }
 }
 impl From<u8> for HiddenState {
+    /// Converts the lowest two bits of a byte to a `HiddenState`, using the
+    /// default for invalid values.
     fn from(x: u8) -> Self {
         match x & 0b11 {
-            0 => HiddenState::Unknown,
             1 => HiddenState::Safe,
             2 => HiddenState::Mine,
-            _ => panic!("Invalid HiddenState"),
+            _ => HiddenState::default(),
         }
     }
 }
@@ -160,8 +266,76 @@ fn test_packed_tile() {
         assert_eq!(t, t.pack().unpack());
     }
 
-    for n in 0..32 {
+    for n in 0..=Tile::MAX_NUMBER {
         let t = Tile::Number(n);
         assert_eq!(t, t.pack().unpack());
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_tile_display() {
+    for &h in &[HiddenState::Unknown, HiddenState::Safe, HiddenState::Mine] {
+        assert_eq!(Tile::Covered(FlagState::None, h).to_string(), "#");
+        assert_eq!(Tile::Covered(FlagState::Flag, h).to_string(), "F");
+        assert_eq!(Tile::Covered(FlagState::Question, h).to_string(), "?");
+    }
+    assert_eq!(Tile::Number(0).to_string(), " ");
+    for n in 1..=9 {
+        assert_eq!(Tile::Number(n).to_string(), n.to_string());
+    }
+    for n in 10..=Tile::MAX_NUMBER {
+        let s = Tile::Number(n).to_string();
+        assert_eq!(s.chars().count(), 1);
+        assert_eq!(
+            Tile::from_char(s.chars().next().unwrap()),
+            Some(Tile::Number(n))
+        );
+    }
+    assert_eq!(Tile::Number(10).to_string(), "\u{10A}");
+    assert_eq!(Tile::Mine.to_string(), "!");
+
+    for c in "#F? 123456789!".chars() {
+        let tile = Tile::from_char(c).unwrap();
+        assert_eq!(tile.to_string(), c.to_string());
+        if let Tile::Covered(_, h) = tile {
+            assert_eq!(h, HiddenState::Unknown);
+        }
+    }
+    for &c in &['+', '0', 'A', '*', '\n', '\u{109}', '\u{1A9}'] {
+        assert_eq!(Tile::from_char(c), None);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_packed_tile_number_ranges() {
+    // The largest numbers in each range must round-trip.
+    let max_ascii = Tile::Number(Tile::MAX_ASCII_NUMBER).pack();
+    assert_eq!(max_ascii, PackedTile(b'_'));
+    assert_eq!(max_ascii.unpack(), Tile::Number(Tile::MAX_ASCII_NUMBER));
+
+    let max = Tile::Number(Tile::MAX_NUMBER).pack();
+    assert_eq!(max, PackedTile(0xFF));
+    assert_eq!(max.unpack(), Tile::Number(Tile::MAX_NUMBER));
+
+    // Anything larger is clamped.
+    assert_eq!(Tile::Number(u8::MAX).pack(), max);
+}
+
+#[cfg(test)]
+#[test]
+fn test_unpack_invalid() {
+    assert_eq!(FlagState::from(3), FlagState::None);
+    assert_eq!(HiddenState::from(3), HiddenState::Unknown);
+
+    // No byte should cause a panic.
+    for b in 0..=u8::MAX {
+        PackedTile(b).unpack();
+    }
+    assert_eq!(PackedTile(b'"').unpack(), Tile::default());
+    assert_eq!(
+        PackedTile(0x6F).unpack(),
+        Tile::Covered(FlagState::None, HiddenState::Unknown),
+    );
+}
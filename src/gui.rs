@@ -1,12 +1,12 @@
 use glium::glutin::event::{Event, StartCause, WindowEvent};
 use glium::glutin::event_loop::{ControlFlow, EventLoop};
-use glium::glutin::window::WindowBuilder;
+use glium::glutin::window::{Icon, WindowBuilder};
 use glium::glutin::ContextBuilder;
 use lazy_static::lazy_static;
 use send_wrapper::SendWrapper;
 use std::cell::RefCell;
 use std::collections::VecDeque;
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 use crate::game::Game;
 use crate::render;
@@ -15,19 +15,93 @@ lazy_static! {
     static ref EVENT_LOOP: SendWrapper<RefCell<Option<EventLoop<()>>>> =
         SendWrapper::new(RefCell::new(Some(EventLoop::new())));
     pub static ref DISPLAY: SendWrapper<glium::Display> = SendWrapper::new({
-        let wb = WindowBuilder::new().with_title(crate::TITLE.to_owned());
+        let wb = WindowBuilder::new()
+            .with_title(crate::TITLE.to_owned())
+            .with_window_icon(load_window_icon());
         let cb = ContextBuilder::new().with_vsync(true);
         glium::Display::new(wb, cb, EVENT_LOOP.borrow().as_ref().unwrap())
             .expect("Failed to initialize display")
     });
 }
 
+fn load_window_icon() -> Option<Icon> {
+    let icon_bytes = include_bytes!("../resources/icon/infinite_minesweeper_64x64.png");
+    let image = image::load_from_memory(icon_bytes).ok()?.to_rgba8();
+    let (width, height) = image.dimensions();
+    Icon::from_rgba(image.into_raw(), width, height).ok()
+}
+
 pub fn show_gui() -> ! {
     let display = &**DISPLAY;
 
     // Initialize runtime data.
-    let mut game = Game::load_from_file();
+    let save_stats = std::env::args().any(|arg| arg == "--stats");
+    let mut args = std::env::args()
+        .skip(1)
+        .filter(|arg| arg != "--stats")
+        .collect::<Vec<_>>();
+    // `--seed <n>` can be combined with any of the other options.
+    let seed = args.iter().position(|arg| arg == "--seed").map(|i| {
+        let seed = args.get(i + 1).cloned().unwrap_or_default();
+        args.drain(i..(i + 2).min(args.len()));
+        seed
+    });
+    let mut args = args.into_iter();
+    let mut game = match args.next().as_deref() {
+        Some("--practice") => Game::practice(),
+        Some("--beginner") => Game::beginner(),
+        Some("--intermediate") => Game::intermediate(),
+        Some("--expert") => Game::expert(),
+        Some("--replay") => {
+            let path = args.next().expect("Missing replay file");
+            match std::fs::read_to_string(&path) {
+                Ok(s) => match s.parse() {
+                    Ok(replay) => {
+                        let mut game = Game::new();
+                        game.replay(replay, 1.0);
+                        game
+                    }
+                    Err(e) => {
+                        eprintln!("Unable to load {}: {}; starting new game", path, e);
+                        Game::new()
+                    }
+                },
+                Err(e) => {
+                    eprintln!("Unable to read {}: {}; starting new game", path, e);
+                    Game::new()
+                }
+            }
+        }
+        Some("--load-text") => {
+            let path = args.next().expect("Missing text file");
+            match std::fs::read_to_string(&path) {
+                Ok(s) => Game::from_dump(&s).unwrap_or_else(|e| {
+                    eprintln!("Unable to load {}: {}; starting new game", path, e);
+                    Game::new()
+                }),
+                Err(e) => {
+                    eprintln!("Unable to read {}: {}; starting new game", path, e);
+                    Game::new()
+                }
+            }
+        }
+        _ => Game::load_from_file(),
+    };
+    match seed.map(|s| s.parse()) {
+        Some(Ok(seed)) => game.set_seed(seed),
+        Some(Err(e)) => eprintln!("Invalid seed: {}; using a random one", e),
+        None => (),
+    }
+    eprintln!("Playing board with seed {}", game.seed());
+    if save_stats {
+        game.settings.stats_file = Game::default_stats_file_path();
+    }
+    game.settings.best_times_file = Game::default_best_times_file_path();
+    game.load_best_times();
     let mut events_buffer = VecDeque::new();
+    let mut window_title = crate::TITLE.to_owned();
+    // Whether the loop is waiting for input because nothing is changing.
+    let mut idle = false;
 
     // Main loop.
     let mut last_frame_time = Instant::now();
@@ -60,14 +134,22 @@ pub fn show_gui() -> ! {
 
             // Queue the event to be handled next time we render
             // everything.
-            Some(ev) => events_buffer.push_back(ev),
+            Some(ev) => {
+                // Input (or a request to redraw) ends idling.
+                let wakes = matches!(ev, Event::WindowEvent { .. } | Event::RedrawRequested(_));
+                if idle && wakes {
+                    idle = false;
+                    *control_flow = ControlFlow::WaitUntil(next_frame_time);
+                }
+                events_buffer.push_back(ev);
+            }
 
             // Ignore this event.
             None => (),
         }
 
         if do_frame && next_frame_time <= now {
-            let frame_duration = Duration::from_secs_f64(1.0 / 60.0);
+            let frame_duration = game.settings.min_frame_duration;
 
             next_frame_time = now + frame_duration;
             if next_frame_time < Instant::now() {
@@ -76,12 +158,23 @@ pub fn show_gui() -> ! {
             }
             *control_flow = ControlFlow::WaitUntil(next_frame_time);
 
-            let frame_duration = now
+            // Measure the real time since the last frame, rather than the time
+            // this frame was scheduled for, which may be from before the loop
+            // went idle.
+            let frame_start = Instant::now();
+            let frame_duration = frame_start
                 .checked_duration_since(last_frame_time)
                 .unwrap_or(frame_duration);
             // TODO: give `frame_duration` to egui if egui wants it
-            last_frame_time = now;
+            last_frame_time = frame_start;
+
+            // Advance the game before handling input, so that input after
+            // idling happens at the right time.
+            game.do_frame(frame_duration);
 
+            let had_input = events_buffer
+                .iter()
+                .any(|ev| matches!(ev, Event::WindowEvent { .. }));
             for ev in events_buffer.drain(..) {
                 // Handle events.
                 match ev {
@@ -96,12 +189,34 @@ pub fn show_gui() -> ! {
                 }
             }
 
-            game.do_frame(frame_duration);
+            // Update the window title.
+            let new_window_title = game.window_title();
+            if new_window_title != window_title {
+                display.gl_window().window().set_title(&new_window_title);
+                window_title = new_window_title;
+            }
 
             // Draw everything.
             let mut target = display.draw();
-            render::draw_grid(&mut target, &game.grid, &mut game.camera);
+            let camera_settled = game.camera_settled();
+            let cursor_tile = game.cursor_tile_pos();
+            let flag_plant_ages = game.flag_plant_ages();
+            render::draw_grid(
+                &mut target,
+                &game.grid,
+                &mut game.camera,
+                &game.settings,
+                camera_settled,
+                cursor_tile,
+                &flag_plant_ages,
+            );
             target.finish().expect("Failed to swap buffers");
+
+            // Stop drawing frames until the next input if nothing is changing.
+            if matches!(*control_flow, ControlFlow::WaitUntil(_)) && game.is_idle(had_input) {
+                idle = true;
+                *control_flow = ControlFlow::Wait;
+            }
         }
     })
 }
@@ -1,33 +1,98 @@
 use glium::{Surface, VertexBuffer};
 use lazy_static::lazy_static;
 use send_wrapper::SendWrapper;
+use std::collections::HashMap;
+use std::time::Duration;
 
+mod palette;
 mod shaders;
 mod textures;
 
-use crate::game::{Camera, ChunkPos, Grid, Tile, TilePos, CHUNK_SIZE};
+use crate::game::{
+    chunks_in_rect, tiles_in_rect, Camera, Chunk, FlagState, Grid, Scale, Settings, Tile, TilePos,
+    CHUNK_SIZE, FLAG_PLANT_DURATION,
+};
+use textures::SpritesheetSampler;
 
 const TILE_BATCH_SIZE: usize = 4096;
 
+/// Color that leaves sprites unchanged.
+const NO_TINT: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+/// Color used to shade alternating covered tiles when the checkerboard setting
+/// is enabled.
+const CHECKERBOARD_TINT: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+/// Color used to shade covered tiles in chunks where mines have not been placed
+/// when the explored setting is enabled.
+const UNEXPLORED_TINT: [f32; 4] = [0.75, 0.75, 0.8, 1.0];
+
+/// Scale factor below which flags are drawn as simple dots and question marks
+/// are hidden, because they would be illegible anyway.
+const SIMPLE_MARKS_MAX_SCALE_FACTOR: f64 = 12.0;
+/// Sprite used to draw a flag as a simple dot. This is a plain square, so it is
+/// shrunk by `FLAG_DOT_SCALE` to leave the covered tile visible around it.
+const FLAG_DOT_SPRITE_COORDS: [u32; 2] = [0, 2];
+/// Size of a flag drawn as a simple dot relative to its tile.
+const FLAG_DOT_SCALE: f32 = 0.5;
+/// Color of a flag drawn as a simple dot.
+const FLAG_DOT_TINT: [f32; 4] = [0.9, 0.1, 0.1, 1.0];
+/// Sprite used to mark the square under the cursor, which is tinted
+/// translucent so the square shows through.
+const CURSOR_SPRITE_COORDS: [u32; 2] = [0, 2];
+/// Sprite used to draw the mine density overlay, which is recolored and tinted
+/// translucent.
+const DENSITY_SPRITE_COORDS: [u32; 2] = [0, 2];
+/// How much a flag bounces while being planted. Larger values make it grow
+/// further past its full size before settling.
+const FLAG_PLANT_OVERSHOOT: f32 = 1.70158;
+
 #[derive(Debug, Copy, Clone)]
 struct Vertex2D {
     pos: [f32; 2],
 }
 glium::implement_vertex!(Vertex2D, pos);
 
-#[derive(Debug, Copy, Clone)]
-struct TileAttr {
+/// Sprite to draw at a tile.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TileAttr {
     tile_coords: [i32; 2],
     sprite_coords: [u32; 2],
+    /// Color to multiply the sprite by.
+    tint: [f32; 4],
+    /// Color to replace the sprite's color with, where the alpha channel is
+    /// how much of the original color to replace.
+    recolor: [f32; 4],
+    /// Size of the sprite relative to the tile, around the center of the tile.
+    scale: f32,
 }
-glium::implement_vertex!(TileAttr, tile_coords, sprite_coords);
+glium::implement_vertex!(TileAttr, tile_coords, sprite_coords, tint, recolor, scale);
 impl TileAttr {
     fn new(tile_coords: [i32; 2], sprite_coords: [u32; 2]) -> Self {
         Self {
             tile_coords,
             sprite_coords,
+            tint: NO_TINT,
+            recolor: palette::NO_RECOLOR,
+            scale: 1.0,
         }
     }
+
+    #[must_use = "This method returns a new value instead of mutating its input"]
+    fn with_tint(mut self, tint: [f32; 4]) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    #[must_use = "This method returns a new value instead of mutating its input"]
+    fn with_recolor(mut self, recolor: [f32; 4]) -> Self {
+        self.recolor = recolor;
+        self
+    }
+
+    #[must_use = "This method returns a new value instead of mutating its input"]
+    fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
 }
 
 lazy_static! {
@@ -53,7 +118,15 @@ lazy_static! {
     );
 }
 
-pub fn draw_grid(target: &mut glium::Frame, grid: &Grid, camera: &mut Camera) {
+pub fn draw_grid(
+    target: &mut glium::Frame,
+    grid: &Grid,
+    camera: &mut Camera,
+    settings: &Settings,
+    camera_settled: bool,
+    cursor_tile: Option<TilePos>,
+    flag_plant_ages: &HashMap<TilePos, Duration>,
+) {
     target.clear_color_srgb(0.2, 0.2, 0.2, 1.0);
 
     // Update target dimensisons and get camera data.
@@ -65,44 +138,15 @@ pub fn draw_grid(target: &mut glium::Frame, grid: &Grid, camera: &mut Camera) {
         ..glium::DrawParameters::default()
     };
 
-    let (target_w, target_h) = target.get_dimensions();
-    let TilePos(mut x1, mut y1) = camera.pixel_to_tile_pos((0, target_h));
-    x1 -= 1;
-    y1 -= 1;
-    let TilePos(mut x2, mut y2) = camera.pixel_to_tile_pos((target_w, 0));
-    x2 += 1;
-    y2 += 1;
-
-    let ChunkPos(chunk_x1, chunk_y1) = TilePos(x1, y1).chunk();
-    let ChunkPos(chunk_x2, chunk_y2) = TilePos(x2, y2).chunk();
-
-    let mut tile_attrs = vec![];
-
-    for chunk_y in chunk_y1..=chunk_y2 {
-        for chunk_x in chunk_x1..=chunk_x2 {
-            let chunk = grid.get_chunk(ChunkPos(chunk_x, chunk_y));
-            for y in 0..CHUNK_SIZE as i32 {
-                for x in 0..CHUNK_SIZE as i32 {
-                    let tile_coords = [
-                        x + chunk_x * CHUNK_SIZE as i32,
-                        y + chunk_y * CHUNK_SIZE as i32,
-                    ];
-                    let tile = match chunk {
-                        Some(c) => c.get_tile(TilePos(x, y)),
-                        None => Tile::default(),
-                    };
-                    let bg_sprite_coords = textures::bg_sprite_coords(tile);
-                    tile_attrs.push(TileAttr::new(tile_coords, bg_sprite_coords));
-                    if let Some(fg_sprite_coords) = textures::fg_sprite_coords(tile) {
-                        tile_attrs.push(TileAttr::new(tile_coords, fg_sprite_coords));
-                    }
-                }
-            }
-        }
+    let mut tile_attrs = collect_visible_tiles(grid, *camera, settings, flag_plant_ages);
+    if settings.show_density {
+        tile_attrs.extend(density_overlay(grid, *camera));
     }
+    tile_attrs.extend(cursor_sprite(cursor_tile, settings));
 
     let uniform = glium::uniform! {
-        spritesheet: **textures::TILES_SPRITESHEET_SAMPLER,
+        spritesheet: spritesheet_sampler(settings, camera_settled).get(),
+        brightness: settings.brightness(),
 
         camera_center: camera.int_center(),
         transform: tile_transform_matrix,
@@ -133,3 +177,409 @@ pub fn draw_grid(target: &mut glium::Frame, grid: &Grid, camera: &mut Camera) {
             .expect("Failed to draw tiles");
     }
 }
+
+/// Returns the sprites to draw for every tile in each chunk visible to the
+/// camera, in the order they should be drawn. Flags that were placed recently
+/// are drawn smaller or larger according to how long ago they were placed.
+pub fn collect_visible_tiles(
+    grid: &Grid,
+    camera: Camera,
+    settings: &Settings,
+    flag_plant_ages: &HashMap<TilePos, Duration>,
+) -> Vec<TileAttr> {
+    let (TilePos(x1, y1), TilePos(x2, y2)) = camera.visible_tile_rect();
+    let min = TilePos(x1 - 1, y1 - 1);
+    let max = TilePos(x2 + 1, y2 + 1);
+
+    let simple_marks = use_simple_marks(camera.scale());
+    let flag_dot_tint = palette::flag_dot_tint(settings.palette).unwrap_or(FLAG_DOT_TINT);
+
+    let mut tile_attrs = vec![];
+
+    for chunk_pos in chunks_in_rect(min, max) {
+        let chunk = grid.get_chunk(chunk_pos);
+        for pos in tiles_in_rect(chunk_pos.min_tile(), chunk_pos.max_tile()) {
+            // Walls outside the arena are left as background.
+            if !grid.in_arena(pos) {
+                continue;
+            }
+            let TilePos(x, y) = pos;
+            let tile_coords = [x, y];
+            let tile = match chunk {
+                Some(c) => c.get_tile(pos),
+                None => Tile::default(),
+            };
+            let bg_sprite_coords = textures::bg_sprite_coords(tile);
+            let bg_tint = match tile {
+                Tile::Covered(_, _) => covered_tint(tile_coords, chunk, settings),
+                _ => NO_TINT,
+            };
+            tile_attrs.push(TileAttr::new(tile_coords, bg_sprite_coords).with_tint(bg_tint));
+            let fg_scale = match tile {
+                Tile::Covered(FlagState::Flag, _) => flag_plant_ages
+                    .get(&pos)
+                    .map_or(1.0, |&age| flag_plant_scale(age)),
+                _ => 1.0,
+            };
+            match tile {
+                Tile::Covered(FlagState::Flag, _) if simple_marks => tile_attrs.push(
+                    TileAttr::new(tile_coords, FLAG_DOT_SPRITE_COORDS)
+                        .with_tint(flag_dot_tint)
+                        .with_scale(FLAG_DOT_SCALE * fg_scale),
+                ),
+                Tile::Covered(FlagState::Question, _) if simple_marks => (),
+                _ => {
+                    if let Some(fg_sprite_coords) = textures::fg_sprite_coords(tile, pos) {
+                        let recolor = palette::fg_recolor(settings.palette, tile);
+                        tile_attrs.push(
+                            TileAttr::new(tile_coords, fg_sprite_coords)
+                                .with_recolor(recolor)
+                                .with_scale(fg_scale),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    tile_attrs
+}
+
+/// Returns the sprites to draw on top of every tile in each chunk visible to
+/// the camera where mines have been placed, colored by the fraction of tiles in
+/// the chunk that are mines.
+fn density_overlay(grid: &Grid, camera: Camera) -> Vec<TileAttr> {
+    let (min, max) = camera.visible_tile_rect();
+    let mut tile_attrs = vec![];
+    for chunk_pos in chunks_in_rect(min, max) {
+        let mine_count = match grid.chunk_mine_count(chunk_pos) {
+            Some(n) => n,
+            None => continue,
+        };
+        let density = mine_count as f64 / (CHUNK_SIZE * CHUNK_SIZE) as f64;
+        let [r, g, b] = palette::density_color(density);
+        let attr = TileAttr::new([0, 0], DENSITY_SPRITE_COORDS)
+            .with_recolor([r, g, b, 1.0])
+            .with_tint([1.0, 1.0, 1.0, palette::DENSITY_ALPHA]);
+        for TilePos(x, y) in tiles_in_rect(chunk_pos.min_tile(), chunk_pos.max_tile()) {
+            if grid.in_arena(TilePos(x, y)) {
+                tile_attrs.push(TileAttr {
+                    tile_coords: [x, y],
+                    ..attr
+                });
+            }
+        }
+    }
+    tile_attrs
+}
+
+/// Returns the sprite to draw on top of the square under the cursor, or `None`
+/// if it is disabled or the cursor is not in the window.
+fn cursor_sprite(cursor_tile: Option<TilePos>, settings: &Settings) -> Option<TileAttr> {
+    if !settings.show_cursor_sprite {
+        return None;
+    }
+    let TilePos(x, y) = cursor_tile?;
+    Some(
+        TileAttr::new([x, y], CURSOR_SPRITE_COORDS)
+            .with_tint(palette::cursor_tint(settings.palette)),
+    )
+}
+
+/// Returns the size of a flag relative to its tile some time after it was
+/// placed. The flag grows from nothing, overshoots its full size, and settles
+/// back to it once `FLAG_PLANT_DURATION` has passed.
+fn flag_plant_scale(age: Duration) -> f32 {
+    if age >= FLAG_PLANT_DURATION {
+        return 1.0;
+    }
+    let t = (age.as_secs_f64() / FLAG_PLANT_DURATION.as_secs_f64()) as f32 - 1.0;
+    let c = FLAG_PLANT_OVERSHOOT;
+    1.0 + (c + 1.0) * t * t * t + c * t * t
+}
+
+/// Returns `true` if tiles should be drawn using the smooth sampler, which
+/// reduces aliasing during motion but looks blurry when still.
+fn use_smooth_sampler(settings: &Settings, camera_settled: bool) -> bool {
+    settings.smooth_motion && !camera_settled
+}
+
+/// Returns the sampler to draw tiles with.
+fn spritesheet_sampler(settings: &Settings, camera_settled: bool) -> SpritesheetSampler {
+    if !settings.mipmaps {
+        SpritesheetSampler::Crisp
+    } else if use_smooth_sampler(settings, camera_settled) {
+        SpritesheetSampler::Smooth
+    } else {
+        SpritesheetSampler::Mipmapped
+    }
+}
+
+/// Returns `true` if flags and question marks should be simplified at a
+/// scale.
+fn use_simple_marks(scale: Scale) -> bool {
+    scale.factor() < SIMPLE_MARKS_MAX_SCALE_FACTOR
+}
+
+/// Returns the color to multiply a covered tile by to make a checkerboard
+/// pattern.
+fn checkerboard_tint([x, y]: [i32; 2]) -> [f32; 4] {
+    if (x + y) & 1 == 0 {
+        NO_TINT
+    } else {
+        CHECKERBOARD_TINT
+    }
+}
+
+/// Returns the color to multiply a covered tile by, given the chunk containing
+/// it.
+fn covered_tint(tile_coords: [i32; 2], chunk: Option<&Chunk>, settings: &Settings) -> [f32; 4] {
+    let mut tint = NO_TINT;
+    if settings.checkerboard {
+        tint = checkerboard_tint(tile_coords);
+    }
+    if settings.show_explored && !chunk.is_some_and(Chunk::all_mines_placed) {
+        for (a, b) in tint.iter_mut().zip(&UNEXPLORED_TINT) {
+            *a *= b;
+        }
+    }
+    tint
+}
+
+#[cfg(test)]
+#[test]
+fn test_checkerboard_tint() {
+    assert_eq!(checkerboard_tint([0, 0]), NO_TINT);
+    assert_eq!(checkerboard_tint([1, 0]), CHECKERBOARD_TINT);
+    assert_eq!(checkerboard_tint([0, -1]), CHECKERBOARD_TINT);
+    assert_eq!(checkerboard_tint([-3, -5]), NO_TINT);
+    assert_eq!(checkerboard_tint([-64, 63]), CHECKERBOARD_TINT);
+}
+
+#[cfg(test)]
+#[test]
+fn test_covered_tint() {
+    let mut settings = Settings::default();
+    let mut grid = Grid::with_seed(0);
+    let chunk_pos = TilePos(0, 0).chunk();
+    grid.place_mines_in_chunk(chunk_pos);
+    let generated = grid.get_chunk(chunk_pos);
+    assert!(generated.unwrap().all_mines_placed());
+    let untouched = Chunk::default();
+    assert!(!untouched.all_mines_placed());
+
+    // Off by default.
+    for &chunk in &[None, Some(&untouched), generated] {
+        assert_eq!(covered_tint([0, 0], chunk, &settings), NO_TINT);
+    }
+
+    settings.show_explored = true;
+    assert_eq!(covered_tint([0, 0], None, &settings), UNEXPLORED_TINT);
+    assert_eq!(
+        covered_tint([0, 0], Some(&untouched), &settings),
+        UNEXPLORED_TINT,
+    );
+    assert_eq!(covered_tint([0, 0], generated, &settings), NO_TINT);
+
+    settings.checkerboard = true;
+    assert_eq!(
+        covered_tint([1, 0], generated, &settings),
+        CHECKERBOARD_TINT
+    );
+    let [r, g, b, a] = covered_tint([1, 0], None, &settings);
+    assert_eq!([r, g, b, a], [0.9 * 0.75, 0.9 * 0.75, 0.9 * 0.8, 1.0]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_cursor_sprite() {
+    use crate::game::Palette;
+
+    let mut settings = Settings::default();
+    assert_eq!(cursor_sprite(Some(TilePos(3, -2)), &settings), None);
+
+    settings.show_cursor_sprite = true;
+    let sprite = cursor_sprite(Some(TilePos(3, -2)), &settings).unwrap();
+    assert_eq!(sprite.tile_coords, [3, -2]);
+    assert_eq!(sprite.sprite_coords, CURSOR_SPRITE_COORDS);
+    assert_eq!(sprite.tint, palette::cursor_tint(Palette::Classic));
+    assert!(sprite.tint[3] < 1.0);
+    assert_eq!(cursor_sprite(None, &settings), None);
+
+    settings.palette = Palette::Tritanopia;
+    let sprite = cursor_sprite(Some(TilePos(3, -2)), &settings).unwrap();
+    assert_ne!(sprite.tint, palette::cursor_tint(Palette::Classic));
+}
+
+#[cfg(test)]
+#[test]
+fn test_collect_visible_tiles() {
+    use crate::game::{FlagState, HiddenState, Scale};
+    use cgmath::Point2;
+
+    let mut camera = Camera::default();
+    camera.set_target_dimensions((10, 10));
+    camera.set_scale(Scale::from_factor(16.0));
+
+    // The tiles around the origin are in four different chunks, none of which
+    // are loaded.
+    let mut grid = Grid::with_seed(0);
+    let mut settings = Settings::default();
+    let tile_attrs = collect_visible_tiles(&grid, camera, &settings, &HashMap::new());
+    assert_eq!(tile_attrs.len(), 4 * 4096);
+    assert!(tile_attrs.iter().all(|t| t.sprite_coords == [1, 2]));
+    assert_eq!(tile_attrs[0].tile_coords, [-64, -64]);
+    assert_eq!(tile_attrs[4095].tile_coords, [-1, -1]);
+    assert_eq!(tile_attrs[4096].tile_coords, [0, -64]);
+
+    // Only one chunk is visible from its center.
+    camera.set_center(Point2::new(32.0, 32.0));
+    grid.set_tile(TilePos(1, 2), Tile::Number(3));
+    grid.set_tile(
+        TilePos(3, 3),
+        Tile::Covered(FlagState::Flag, HiddenState::Safe),
+    );
+    grid.set_tile(TilePos(5, 3), Tile::Number(0));
+    settings.checkerboard = true;
+    let tile_attrs = collect_visible_tiles(&grid, camera, &settings, &HashMap::new());
+    assert_eq!(tile_attrs.len(), 4096 + 2);
+    assert_eq!(
+        tile_attrs[..5],
+        [
+            TileAttr::new([0, 0], [1, 2]),
+            TileAttr::new([1, 0], [1, 2]).with_tint(CHECKERBOARD_TINT),
+            TileAttr::new([2, 0], [1, 2]),
+            TileAttr::new([3, 0], [1, 2]).with_tint(CHECKERBOARD_TINT),
+            TileAttr::new([4, 0], [1, 2]),
+        ],
+    );
+    assert_eq!(
+        tile_attrs[64 * 2..64 * 2 + 3],
+        [
+            TileAttr::new([0, 2], [1, 2]),
+            TileAttr::new([1, 2], [0, 2]),
+            TileAttr::new([1, 2], [2, 0]),
+        ],
+    );
+    assert_eq!(
+        tile_attrs[64 * 3 + 1..64 * 3 + 7],
+        [
+            TileAttr::new([0, 3], [1, 2]).with_tint(CHECKERBOARD_TINT),
+            TileAttr::new([1, 3], [1, 2]),
+            TileAttr::new([2, 3], [1, 2]).with_tint(CHECKERBOARD_TINT),
+            TileAttr::new([3, 3], [1, 2]),
+            TileAttr::new([3, 3], [0, 1]),
+            TileAttr::new([4, 3], [1, 2]).with_tint(CHECKERBOARD_TINT),
+        ],
+    );
+    assert_eq!(tile_attrs[64 * 3 + 7], TileAttr::new([5, 3], [0, 2]));
+}
+
+#[cfg(test)]
+#[test]
+fn test_simple_marks() {
+    use crate::game::HiddenState;
+    use cgmath::Point2;
+
+    assert!(use_simple_marks(Scale::from_factor(8.0)));
+    assert!(use_simple_marks(Scale::from_factor(11.9)));
+    assert!(!use_simple_marks(Scale::from_factor(12.0)));
+    assert!(!use_simple_marks(Scale::from_factor(16.0)));
+
+    let mut grid = Grid::with_seed(0);
+    grid.set_tile(
+        TilePos(0, 0),
+        Tile::Covered(FlagState::Flag, HiddenState::Safe),
+    );
+    grid.set_tile(
+        TilePos(1, 0),
+        Tile::Covered(FlagState::Question, HiddenState::Safe),
+    );
+    let mut camera = Camera::default();
+    camera.set_center(Point2::new(32.0, 32.0));
+    camera.set_scale(Scale::from_factor(8.0));
+    let tile_attrs = collect_visible_tiles(&grid, camera, &Settings::default(), &HashMap::new());
+    assert_eq!(
+        tile_attrs[..3],
+        [
+            TileAttr::new([0, 0], [1, 2]),
+            TileAttr::new([0, 0], FLAG_DOT_SPRITE_COORDS)
+                .with_tint(FLAG_DOT_TINT)
+                .with_scale(FLAG_DOT_SCALE),
+            TileAttr::new([1, 0], [1, 2]),
+        ],
+    );
+    assert_eq!(tile_attrs[3].tile_coords, [2, 0]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_flag_plant_scale() {
+    use crate::game::HiddenState;
+    use cgmath::Point2;
+
+    let ms = Duration::from_millis;
+    assert_eq!(flag_plant_scale(ms(0)), 0.0);
+    assert!(flag_plant_scale(ms(20)) < flag_plant_scale(ms(40)));
+    // Bounces past full size and back.
+    assert!((flag_plant_scale(ms(60)) - 1.0877).abs() < 1e-3);
+    assert!(flag_plant_scale(ms(110)) > 1.0);
+    assert_eq!(flag_plant_scale(FLAG_PLANT_DURATION), 1.0);
+    assert_eq!(flag_plant_scale(ms(1000)), 1.0);
+
+    // Only the flag is scaled, not the tile under it.
+    let mut grid = Grid::with_seed(0);
+    grid.set_tile(
+        TilePos(0, 0),
+        Tile::Covered(FlagState::Flag, HiddenState::Safe),
+    );
+    let mut camera = Camera::default();
+    camera.set_center(Point2::new(32.0, 32.0));
+    let mut ages = HashMap::new();
+    ages.insert(TilePos(0, 0), ms(60));
+    let tile_attrs = collect_visible_tiles(&grid, camera, &Settings::default(), &ages);
+    assert_eq!(
+        tile_attrs[..2],
+        [
+            TileAttr::new([0, 0], [1, 2]),
+            TileAttr::new([0, 0], [0, 1]).with_scale(flag_plant_scale(ms(60))),
+        ],
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_use_smooth_sampler() {
+    let mut settings = Settings::default();
+    assert!(!use_smooth_sampler(&settings, true));
+    assert!(!use_smooth_sampler(&settings, false));
+    settings.smooth_motion = true;
+    assert!(!use_smooth_sampler(&settings, true));
+    assert!(use_smooth_sampler(&settings, false));
+}
+
+#[cfg(test)]
+#[test]
+fn test_spritesheet_sampler() {
+    let mut settings = Settings::default();
+    assert_eq!(
+        spritesheet_sampler(&settings, false),
+        SpritesheetSampler::Mipmapped,
+    );
+    settings.smooth_motion = true;
+    assert_eq!(
+        spritesheet_sampler(&settings, false),
+        SpritesheetSampler::Smooth,
+    );
+    assert_eq!(
+        spritesheet_sampler(&settings, true),
+        SpritesheetSampler::Mipmapped,
+    );
+    settings.mipmaps = false;
+    for &camera_settled in &[false, true] {
+        assert_eq!(
+            spritesheet_sampler(&settings, camera_settled),
+            SpritesheetSampler::Crisp,
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use crate::game::{FlagState, Palette, Tile, MINE_DENSITY};
+
+/// Color that leaves sprite colors unchanged when used as a recolor.
+pub const NO_RECOLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.0];
+
+/// Colors to draw sprites with instead of their original colors.
+struct PaletteColors {
+    /// Colors of numbers 1 through 8.
+    numbers: [[f32; 3]; 8],
+    /// Color of flags.
+    flag: [f32; 3],
+    /// Color of revealed mines.
+    mine: [f32; 3],
+    /// Color of the marker on the square under the cursor.
+    cursor: [f32; 3],
+}
+
+/// Color of the marker on the square under the cursor in the classic palette.
+const CLASSIC_CURSOR_COLOR: [f32; 3] = [1.0, 0.9, 0.0];
+/// Opacity of the marker on the square under the cursor.
+const CURSOR_ALPHA: f32 = 0.4;
+
+/// Colors of the mine density overlay at increasing densities, with the
+/// usual density in the middle. Colors are blended between these.
+const DENSITY_COLOR_STOPS: &[(f64, [f32; 3])] = &[
+    (0.0, [0.2, 0.4, 1.0]),          // blue
+    (MINE_DENSITY, [0.1, 0.8, 0.2]), // green
+    (0.5, [1.0, 0.1, 0.1]),          // red
+];
+/// Opacity of the mine density overlay.
+pub const DENSITY_ALPHA: f32 = 0.4;
+
+/// Colors from the Okabe-Ito palette, which is distinguishable with
+/// red-green color blindness.
+const RED_GREEN_SAFE_COLORS: PaletteColors = PaletteColors {
+    numbers: [
+        [0.000, 0.447, 0.698], // blue
+        [0.902, 0.624, 0.000], // orange
+        [0.800, 0.475, 0.655], // reddish purple
+        [0.337, 0.706, 0.914], // sky blue
+        [0.835, 0.369, 0.000], // vermillion
+        [0.000, 0.620, 0.451], // bluish green
+        [0.000, 0.000, 0.000], // black
+        [0.500, 0.500, 0.500], // gray
+    ],
+    flag: [0.902, 0.624, 0.000],
+    mine: [0.000, 0.000, 0.000],
+    cursor: [0.337, 0.706, 0.914],
+};
+/// Colors that are distinguishable with blue-yellow color blindness.
+const BLUE_YELLOW_SAFE_COLORS: PaletteColors = PaletteColors {
+    numbers: [
+        [0.000, 0.447, 0.698], // blue
+        [0.000, 0.620, 0.451], // green
+        [0.835, 0.102, 0.110], // red
+        [0.267, 0.000, 0.467], // purple
+        [0.600, 0.200, 0.000], // brown
+        [0.000, 0.600, 0.600], // teal
+        [0.000, 0.000, 0.000], // black
+        [0.500, 0.500, 0.500], // gray
+    ],
+    flag: [0.835, 0.102, 0.110],
+    mine: [0.000, 0.000, 0.000],
+    cursor: [0.000, 0.600, 0.600],
+};
+/// Colors for deuteranopia (red-green color blindness with weak green).
+const DEUTERANOPIA_COLORS: PaletteColors = RED_GREEN_SAFE_COLORS;
+/// Colors for protanopia (red-green color blindness with weak red). Reds look
+/// dark with protanopia, so this uses olive instead of vermillion.
+const PROTANOPIA_COLORS: PaletteColors = PaletteColors {
+    numbers: [
+        [0.000, 0.447, 0.698], // blue
+        [0.902, 0.624, 0.000], // orange
+        [0.800, 0.475, 0.655], // reddish purple
+        [0.337, 0.706, 0.914], // sky blue
+        [0.545, 0.490, 0.000], // olive
+        [0.000, 0.620, 0.451], // bluish green
+        [0.000, 0.000, 0.000], // black
+        [0.500, 0.500, 0.500], // gray
+    ],
+    flag: [0.902, 0.624, 0.000],
+    mine: [0.000, 0.000, 0.000],
+    cursor: [0.337, 0.706, 0.914],
+};
+/// Colors for tritanopia (blue-yellow color blindness).
+const TRITANOPIA_COLORS: PaletteColors = BLUE_YELLOW_SAFE_COLORS;
+
+fn palette_colors(palette: Palette) -> Option<&'static PaletteColors> {
+    match palette {
+        Palette::Classic => None,
+        Palette::Deuteranopia => Some(&DEUTERANOPIA_COLORS),
+        Palette::Protanopia => Some(&PROTANOPIA_COLORS),
+        Palette::Tritanopia => Some(&TRITANOPIA_COLORS),
+    }
+}
+
+/// Returns the color to draw the foreground sprite of a tile with, where the
+/// alpha channel is how much of the original color to replace.
+pub fn fg_recolor(palette: Palette, tile: Tile) -> [f32; 4] {
+    let colors = match palette_colors(palette) {
+        Some(c) => c,
+        None => return NO_RECOLOR,
+    };
+    let [r, g, b] = match tile {
+        Tile::Covered(FlagState::Flag, _) => colors.flag,
+        Tile::Covered(_, _) | Tile::Number(0) => return NO_RECOLOR,
+        Tile::Number(n) => colors.numbers[(n as usize - 1) % colors.numbers.len()],
+        Tile::Mine => colors.mine,
+    };
+    [r, g, b, 1.0]
+}
+
+/// Returns the color to multiply a flag drawn as a simple dot by, or `None` to
+/// use the default.
+pub fn flag_dot_tint(palette: Palette) -> Option<[f32; 4]> {
+    let [r, g, b] = palette_colors(palette)?.flag;
+    Some([r, g, b, 1.0])
+}
+
+/// Returns the color to multiply the marker on the square under the cursor
+/// by.
+pub fn cursor_tint(palette: Palette) -> [f32; 4] {
+    let [r, g, b] = palette_colors(palette).map_or(CLASSIC_CURSOR_COLOR, |c| c.cursor);
+    [r, g, b, CURSOR_ALPHA]
+}
+
+/// Returns the color of the mine density overlay for a fraction of tiles that
+/// are mines.
+pub fn density_color(density: f64) -> [f32; 3] {
+    let (mut prev_density, mut prev_color) = DENSITY_COLOR_STOPS[0];
+    if density <= prev_density {
+        return prev_color;
+    }
+    for &(stop_density, stop_color) in &DENSITY_COLOR_STOPS[1..] {
+        if density <= stop_density {
+            let t = ((density - prev_density) / (stop_density - prev_density)) as f32;
+            let mix = |i: usize| prev_color[i] * (1.0 - t) + stop_color[i] * t;
+            return [mix(0), mix(1), mix(2)];
+        }
+        prev_density = stop_density;
+        prev_color = stop_color;
+    }
+    prev_color
+}
+
+#[cfg(test)]
+#[test]
+fn test_fg_recolor() {
+    use crate::game::HiddenState;
+
+    for n in 1..=8 {
+        assert_eq!(fg_recolor(Palette::Classic, Tile::Number(n)), NO_RECOLOR);
+        for &palette in &[
+            Palette::Deuteranopia,
+            Palette::Protanopia,
+            Palette::Tritanopia,
+        ] {
+            let color = fg_recolor(palette, Tile::Number(n));
+            assert_ne!(color, NO_RECOLOR);
+            assert_eq!(color[3], 1.0);
+        }
+    }
+    assert_ne!(
+        fg_recolor(Palette::Deuteranopia, Tile::Number(3)),
+        fg_recolor(Palette::Tritanopia, Tile::Number(3)),
+    );
+    assert_ne!(
+        fg_recolor(Palette::Deuteranopia, Tile::Number(5)),
+        fg_recolor(Palette::Protanopia, Tile::Number(5)),
+    );
+    assert_ne!(
+        fg_recolor(Palette::Deuteranopia, Tile::Number(1)),
+        fg_recolor(Palette::Deuteranopia, Tile::Number(2)),
+    );
+
+    let flag = Tile::Covered(FlagState::Flag, HiddenState::Safe);
+    assert_eq!(fg_recolor(Palette::Classic, flag), NO_RECOLOR);
+    assert_eq!(
+        fg_recolor(Palette::Protanopia, flag),
+        flag_dot_tint(Palette::Protanopia).unwrap(),
+    );
+    assert_eq!(flag_dot_tint(Palette::Classic), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_density_color() {
+    assert_eq!(density_color(0.0), [0.2, 0.4, 1.0]);
+    assert_eq!(density_color(0.2), [0.1, 0.8, 0.2]);
+    assert_eq!(density_color(0.5), [1.0, 0.1, 0.1]);
+
+    // Colors are blended in between and clamped outside.
+    let [r, g, b] = density_color(0.1);
+    assert!((r - 0.15).abs() < 1e-6 && (g - 0.6).abs() < 1e-6 && (b - 0.6).abs() < 1e-6);
+    assert_eq!(density_color(-1.0), density_color(0.0));
+    assert_eq!(density_color(1.0), density_color(0.5));
+}
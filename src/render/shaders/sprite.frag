@@ -1,9 +1,15 @@
 #version 140
 
 in vec2 uv;
+in vec4 v_tint;
+in vec4 v_recolor;
 
 uniform sampler2D spritesheet;
+uniform float brightness;
 
 void main() {
-    gl_FragColor = texture(spritesheet, uv);
+    vec4 color = texture(spritesheet, uv);
+    color.rgb = mix(color.rgb, v_recolor.rgb, v_recolor.a);
+    color *= v_tint;
+    gl_FragColor = vec4(color.rgb * brightness, color.a);
 }
@@ -3,6 +3,9 @@
 in vec2 pos;
 in ivec2 tile_coords;
 in uvec2 sprite_coords;
+in vec4 tint;
+in vec4 recolor;
+in float scale;
 
 uniform sampler2D spritesheet;
 
@@ -10,10 +13,16 @@ uniform ivec2 camera_center;
 uniform mat4 transform;
 
 out vec2 uv;
+out vec4 v_tint;
+out vec4 v_recolor;
 
 const float SPRITE_SIZE = 64.0;
 
 void main() {
-    gl_Position = transform * vec4(pos + vec2(tile_coords - camera_center), 0.0, 1.0);
+    // Scale around the center of the tile.
+    vec2 scaled_pos = (pos - 0.5) * scale + 0.5;
+    gl_Position = transform * vec4(scaled_pos + vec2(tile_coords - camera_center), 0.0, 1.0);
     uv = (SPRITE_SIZE * (sprite_coords + pos)) / vec2(textureSize(spritesheet, 0));
+    v_tint = tint;
+    v_recolor = recolor;
 }
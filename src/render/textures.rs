@@ -3,7 +3,11 @@ use glium::uniforms::{MinifySamplerFilter, Sampler};
 use lazy_static::lazy_static;
 use send_wrapper::SendWrapper;
 
-use crate::game::{FlagState, Tile};
+use crate::game::{FlagState, Tile, TilePos};
+
+/// Sprites used to draw revealed mines. Each mine uses one chosen by its
+/// position, so that a field of mines looks varied.
+const MINE_SPRITE_VARIANTS: &[[u32; 2]] = &[[2, 1]];
 
 fn write_tex_mipmap(t: &SrgbTexture2d, level: u32, image: RawImage2d<'_, u8>) {
     let mipmap_level = t.mipmap(level).expect("Missing mipmap level");
@@ -50,12 +54,57 @@ lazy_static! {
 
         SendWrapper::new(t)
     };
+    /// Spritesheet texture for tiles, without any smaller mipmap levels.
+    static ref TILES_SPRITESHEET_TEX_NO_MIPMAPS: SendWrapper<SrgbTexture2d> = {
+        let raw_img_64 = include_bytes!("../../resources/tilemaps/tiles_64.png");
+
+        let t = SrgbTexture2d::with_mipmaps(
+            &**crate::DISPLAY,
+            load_rgba_image(raw_img_64),
+            MipmapsOption::NoMipmap,
+        )
+        .expect("Failed to create texture");
+
+        SendWrapper::new(t)
+    };
 
     /// Mipmapped texture sampler for the tiles spritesheet.
     pub static ref TILES_SPRITESHEET_SAMPLER: SendWrapper<Sampler<'static, SrgbTexture2d>> =
         SendWrapper::new(TILES_SPRITESHEET_TEX
             .sampled()
             .minify_filter(MinifySamplerFilter::NearestMipmapNearest));
+    /// Mipmapped texture sampler for the tiles spritesheet that blends between
+    /// pixels and mipmap levels, for use while the camera is moving.
+    pub static ref TILES_SPRITESHEET_SMOOTH_SAMPLER: SendWrapper<Sampler<'static, SrgbTexture2d>> =
+        SendWrapper::new(TILES_SPRITESHEET_TEX
+            .sampled()
+            .minify_filter(MinifySamplerFilter::LinearMipmapLinear));
+    /// Texture sampler for the tiles spritesheet that never uses mipmaps.
+    pub static ref TILES_SPRITESHEET_CRISP_SAMPLER: SendWrapper<Sampler<'static, SrgbTexture2d>> =
+        SendWrapper::new(TILES_SPRITESHEET_TEX_NO_MIPMAPS
+            .sampled()
+            .minify_filter(MinifySamplerFilter::Nearest));
+}
+
+/// Texture sampler to use for the tiles spritesheet.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpritesheetSampler {
+    /// Picks the nearest pixel from the nearest mipmap level.
+    Mipmapped,
+    /// Blends between pixels and mipmap levels.
+    Smooth,
+    /// Picks the nearest pixel from the full-size spritesheet.
+    Crisp,
+}
+impl SpritesheetSampler {
+    /// Returns the sampler, creating the texture if it hasn't been used yet.
+    pub fn get(self) -> Sampler<'static, SrgbTexture2d> {
+        match self {
+            SpritesheetSampler::Mipmapped => **TILES_SPRITESHEET_SAMPLER,
+            SpritesheetSampler::Smooth => **TILES_SPRITESHEET_SMOOTH_SAMPLER,
+            SpritesheetSampler::Crisp => **TILES_SPRITESHEET_CRISP_SAMPLER,
+        }
+    }
 }
 
 pub fn bg_sprite_coords(tile: Tile) -> [u32; 2] {
@@ -64,7 +113,7 @@ pub fn bg_sprite_coords(tile: Tile) -> [u32; 2] {
         Tile::Number(_) | Tile::Mine => [0, 2],
     }
 }
-pub fn fg_sprite_coords(tile: Tile) -> Option<[u32; 2]> {
+pub fn fg_sprite_coords(tile: Tile, pos: TilePos) -> Option<[u32; 2]> {
     match tile {
         Tile::Covered(f, _) => match f {
             FlagState::None => None,
@@ -73,6 +122,39 @@ pub fn fg_sprite_coords(tile: Tile) -> Option<[u32; 2]> {
         },
         Tile::Number(0) => None,
         Tile::Number(i) => Some([i as u32 - 1, 0]),
-        Tile::Mine => Some([2, 1]),
+        Tile::Mine => Some(MINE_SPRITE_VARIANTS[sprite_variant(pos, MINE_SPRITE_VARIANTS.len())]),
+    }
+}
+
+/// Returns a number less than `variant_count` that depends only on the
+/// position of a tile, for picking between several sprites for the same kind
+/// of tile.
+fn sprite_variant(TilePos(x, y): TilePos, variant_count: usize) -> usize {
+    // Mix the coordinates using arbitrary large odd constants.
+    let mut hash = (x as u32).wrapping_mul(0x9E37_79B1) ^ (y as u32).wrapping_mul(0x85EB_CA77);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0xC2B2_AE3D);
+    hash ^= hash >> 13;
+    hash as usize % variant_count
+}
+
+#[cfg(test)]
+#[test]
+fn test_sprite_variant() {
+    use crate::game::tiles_in_rect;
+
+    for variant_count in 1..=5 {
+        let mut seen = vec![false; variant_count];
+        for pos in tiles_in_rect(TilePos(-10, -10), TilePos(10, 10)) {
+            let variant = sprite_variant(pos, variant_count);
+            assert!(variant < variant_count);
+            assert_eq!(variant, sprite_variant(pos, variant_count));
+            seen[variant] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
     }
+    assert_eq!(
+        fg_sprite_coords(Tile::Mine, TilePos(-7, 3)),
+        Some(MINE_SPRITE_VARIANTS[0]),
+    );
 }